@@ -0,0 +1,244 @@
+//! Derive macros for `place`
+//!
+//! These are re-exported by `place` with its `derive` feature, which is how
+//! they should be used.
+
+use proc_macro::{Delimiter, Group, Punct, Spacing, Span, TokenStream, TokenTree};
+
+/// Records the order the fields of a struct are declared in, for
+/// `place_decl_order!`
+///
+/// This defines a hidden macro with the same name as the struct, which is
+/// imported along with it, so `place_decl_order!` can be used wherever the
+/// struct can be named within the crate that defines it.
+#[proc_macro_derive(FieldOrder)]
+pub fn field_order(input: TokenStream) -> TokenStream {
+    match parse_struct(input) {
+        Ok((name, fields)) => fields_macro(&name, &fields),
+        Err((span, msg)) => compile_error(span, msg),
+    }
+}
+
+/// Records the fields of a struct, for `emplace_default!`
+///
+/// This defines the same macro as `FieldOrder`, so either may be used with
+/// both `emplace_default!` and `place_decl_order!`, but not both derived.
+#[proc_macro_derive(PlaceDefault)]
+pub fn place_default(input: TokenStream) -> TokenStream {
+    match parse_struct(input) {
+        Ok((name, fields)) => fields_macro(&name, &fields),
+        Err((span, msg)) => compile_error(span, msg),
+    }
+}
+
+/// Writes the fields collected by `place_decl_order!` in the order given
+///
+/// The input is `[order] [callback] (buf) [path] { lets } [entries] [base]`,
+/// where each entry is `(field) { tokens }`. Entries named in `order` are
+/// sorted into it, and the rest left after them for the callback to reject.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __place_sort_fields(input: TokenStream) -> TokenStream {
+    let mut groups = input.into_iter().map(|t| match t {
+        TokenTree::Group(g) => g,
+        t => panic!("expected a group, found `{t}`"),
+    });
+    let mut next = move || groups.next().expect("missing group").stream();
+    let order: Vec<String> = next().into_iter().map(|t| t.to_string()).collect();
+    let callback = next();
+    let buf = next();
+    let path = next();
+    let lets = next();
+    let entries = next();
+    let base = next();
+
+    let mut entries: Vec<(usize, TokenStream)> = {
+        let mut tokens = entries.into_iter();
+        let mut entries = Vec::new();
+        while let (Some(TokenTree::Group(name)), Some(TokenTree::Group(entry))) =
+            (tokens.next(), tokens.next())
+        {
+            let name = name.stream().to_string();
+            let pos = order.iter().position(|f| *f == name);
+            entries.push((pos.unwrap_or(order.len()), entry.stream()));
+        }
+        entries
+    };
+    // Stable, so fields given more than once under `#[cfg]` keep their order
+    entries.sort_by_key(|(pos, _)| *pos);
+
+    let mut fields = lets;
+    for (_, entry) in entries {
+        fields.extend(entry);
+        fields.extend([punct(',')]);
+    }
+    fields.extend(base);
+
+    let mut args = buf;
+    args.extend([punct(',')]);
+    args.extend(path);
+    args.extend([group(Delimiter::Brace, fields)]);
+
+    let mut out = callback;
+    out.extend([punct('!'), group(Delimiter::Parenthesis, args)]);
+    out
+}
+
+/// The name of the struct in `input`, and its fields in declaration order
+fn parse_struct(input: TokenStream) -> Result<(String, Vec<String>), (Span, &'static str)> {
+    const NAMED: &str = "this can only be derived for structs with named fields";
+
+    let mut tokens = input.into_iter().peekable();
+    // Attributes are groups, so the first `struct` is the keyword
+    let name = loop {
+        match tokens.next() {
+            Some(TokenTree::Ident(i)) if i.to_string() == "struct" => match tokens.next() {
+                Some(TokenTree::Ident(name)) => break name,
+                _ => return Err((i.span(), NAMED)),
+            },
+            Some(TokenTree::Ident(i)) if matches!(&*i.to_string(), "enum" | "union") => {
+                return Err((i.span(), NAMED));
+            }
+            Some(_) => {}
+            None => return Err((Span::call_site(), NAMED)),
+        }
+    };
+
+    // Generics may contain parentheses, such as `F: Fn(u8)`, so are skipped
+    // before looking for the body
+    if matches!(tokens.peek(), Some(TokenTree::Punct(p)) if p.as_char() == '<') {
+        let mut depth = 0;
+        let mut arrow = false;
+        for t in tokens.by_ref() {
+            if let TokenTree::Punct(p) = &t {
+                match p.as_char() {
+                    '<' => depth += 1,
+                    '>' if !arrow => depth -= 1,
+                    _ => {}
+                }
+                arrow = p.as_char() == '-' && p.spacing() == Spacing::Joint;
+                if depth == 0 {
+                    break;
+                }
+            } else {
+                arrow = false;
+            }
+        }
+    }
+
+    // Tuple structs have their fields before any `where` clause, which may
+    // itself contain parentheses
+    let mut bounds = false;
+    let body = loop {
+        match tokens.next() {
+            Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => break g,
+            Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis && !bounds => {
+                return Err((g.span(), NAMED));
+            }
+            Some(TokenTree::Ident(i)) if i.to_string() == "where" => bounds = true,
+            Some(TokenTree::Punct(p)) if p.as_char() == ';' => return Err((p.span(), NAMED)),
+            Some(_) => {}
+            None => return Err((name.span(), NAMED)),
+        }
+    };
+
+    let mut fields = Vec::new();
+    for field in split_fields(body.stream()) {
+        let mut field = field.into_iter();
+        while let Some(t) = field.next() {
+            match t {
+                // An attribute, followed by its group
+                TokenTree::Punct(p) if p.as_char() == '#' => {
+                    field.next();
+                }
+                TokenTree::Ident(i) if i.to_string() == "pub" => {}
+                // `pub(crate)`
+                TokenTree::Group(_) => {}
+                TokenTree::Ident(i) => {
+                    fields.push(i.to_string());
+                    break;
+                }
+                _ => break,
+            }
+        }
+    }
+    Ok((name.to_string(), fields))
+}
+
+/// Splits the fields of a struct at the commas between them
+///
+/// Commas in the generics of a field's type, such as `HashMap<K, V>`, aren't
+/// in a group, so angle brackets are counted.
+fn split_fields(body: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut fields = vec![Vec::new()];
+    let mut depth = 0usize;
+    let mut arrow = false;
+    for t in body {
+        if let TokenTree::Punct(p) = &t {
+            match p.as_char() {
+                '<' => depth += 1,
+                '>' if !arrow => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => {
+                    fields.push(Vec::new());
+                    arrow = false;
+                    continue;
+                }
+                _ => {}
+            }
+            arrow = p.as_char() == '-' && p.spacing() == Spacing::Joint;
+        } else {
+            arrow = false;
+        }
+        fields.last_mut().unwrap().push(t);
+    }
+    fields
+}
+
+/// The macro named `name` that gives the macros of `place` its `fields`, in
+/// declaration order
+///
+/// It's defined in a module of its own, so that importing it doesn't conflict
+/// with the struct, and calls back into `place` so as not to name the crate.
+fn fields_macro(name: &str, fields: &[String]) -> TokenStream {
+    let module = format!("__place_fields_{}", name.trim_start_matches("r#"));
+    let fields = fields.join(" ");
+    format!(
+        "
+        #[doc(hidden)]
+        #[allow(non_snake_case)]
+        mod {module} {{
+            macro_rules! {name} {{
+                (@fields [$($callback:tt)*] $($t:tt)*) => {{
+                    $($callback)*!([{fields}] $($t)*)
+                }};
+            }}
+            pub(crate) use {name};
+        }}
+        #[doc(hidden)]
+        #[allow(unused_imports)]
+        pub(crate) use {module}::{name};
+        "
+    )
+    .parse()
+    .expect("valid tokens")
+}
+
+fn compile_error(span: Span, msg: &str) -> TokenStream {
+    let out: TokenStream = format!("::core::compile_error!({msg:?});")
+        .parse()
+        .expect("valid tokens");
+    out.into_iter()
+        .map(|mut t| {
+            t.set_span(span);
+            t
+        })
+        .collect()
+}
+
+fn punct(c: char) -> TokenTree {
+    TokenTree::Punct(Punct::new(c, Spacing::Alone))
+}
+
+fn group(delimiter: Delimiter, stream: TokenStream) -> TokenTree {
+    TokenTree::Group(Group::new(delimiter, stream))
+}
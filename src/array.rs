@@ -0,0 +1,833 @@
+//! Placing arrays element by element
+
+use crate::PlaceError;
+use core::{mem::MaybeUninit, ptr};
+
+/// Initialize an array in-place at `buf` element by element, and return a
+/// mutable reference
+///
+/// `buf` is a MaybeUninit of your array, or a mutable reference to one, whose
+/// length is inferred. The closure-like initializer is bound to the index of each element, and
+/// run for each in order.
+///
+/// If an initializer panics, the elements already written are dropped,
+/// and `buf` is left uninitialized.
+///
+/// It is your responsibility to drop your array if needed when you're done
+/// with it.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_array;
+/// # use std::mem::MaybeUninit;
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut [String; 4] = place_array!(buf, |i| i.to_string());
+/// assert_eq!(x, &["0", "1", "2", "3"]);
+///
+/// # // SAFETY: buf has been initialized above
+/// # unsafe { buf.assume_init_drop() };
+/// ```
+#[macro_export]
+macro_rules! place_array {
+    ($buf:expr, |$i:pat_param| $val:expr) => {{
+        use $crate::__private::AsPlace as _;
+        $crate::__private::place_array($buf.__as_place(), |$i: usize| $val)
+    }};
+}
+
+/// Initialize a two-dimensional array in-place at `buf` element by element,
+/// and return a mutable reference
+///
+/// This works exactly like [`place_array!`], for an array of arrays such as
+/// `[[T; M]; N]`, with the initializer bound to the index into the outer and
+/// inner arrays of each element. Elements are written in order, row by row,
+/// each directly at its final location.
+///
+/// If an initializer panics, all the elements already written are dropped,
+/// and `buf` is left uninitialized.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_grid;
+/// # use std::mem::MaybeUninit;
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut [[(usize, usize); 3]; 2] = place_grid!(buf, |i, j| (i, j));
+/// assert_eq!(x[1], [(1, 0), (1, 1), (1, 2)]);
+/// ```
+///
+/// As a field, it can be placed with [`emplace_fn!`]
+///
+/// ```rust
+/// # use place::{place, place_grid};
+/// # use std::mem::MaybeUninit;
+///
+/// struct Grid {
+///     name: String,
+///     cells: [[u32; 16]; 16],
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut Grid = place!(
+///     buf,
+///     Grid {
+///         name: String::from("works"),
+///         cells: emplace_fn!(|buf| place_grid!(*buf, |i, j| (i * 16 + j) as u32)),
+///     }
+/// );
+/// assert_eq!(x.cells[2][3], 35);
+///
+/// # // SAFETY: buf has been initialized above
+/// # unsafe { buf.assume_init_drop() };
+/// ```
+///
+/// [`place_array!`]: crate::place_array
+/// [`emplace_fn!`]: crate::emplace_fn
+#[macro_export]
+macro_rules! place_grid {
+    ($buf:expr, |$i:pat_param, $j:pat_param| $val:expr) => {{
+        use $crate::__private::AsPlace as _;
+        $crate::__private::place_grid($buf.__as_place(), |$i: usize, $j: usize| $val)
+    }};
+}
+
+/// Initialize an array in-place at `buf` from the items of an iterator, and
+/// return a mutable reference
+///
+/// `buf` is a MaybeUninit of your array, whose length is inferred. Exactly as
+/// many items as the length are taken from the iterator, and written in
+/// order. Any further items are not consumed, so passing `&mut iter` leaves
+/// them in `iter`.
+///
+/// If the iterator panics, the elements already written are dropped, and `buf`
+/// is left uninitialized.
+///
+/// # Errors
+///
+/// If the iterator runs out early, the elements already written are dropped,
+/// `buf` is left uninitialized, and the number of items produced is returned.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_each;
+/// # use std::mem::MaybeUninit;
+///
+/// let mut buf = MaybeUninit::uninit();
+/// let mut words = "a b c d e".split(' ').map(String::from);
+///
+/// let x: Result<&mut [String; 4], usize> = place_each!(buf, &mut words);
+/// assert_eq!(x.unwrap(), &["a", "b", "c", "d"]);
+/// assert_eq!(words.next().as_deref(), Some("e"));
+///
+/// # // SAFETY: buf has been initialized above
+/// # unsafe { buf.assume_init_drop() };
+///
+/// let mut buf = MaybeUninit::<[String; 4]>::uninit();
+/// assert_eq!(place_each!(buf, words), Err(0));
+/// ```
+#[macro_export]
+macro_rules! place_each {
+    ($buf:expr, $iter:expr $(,)?) => {{
+        use $crate::__private::AsPlace as _;
+        $crate::__private::place_each($buf.__as_place(), $iter)
+    }};
+}
+
+/// Initialize several slots of the same struct type in-place at the start of
+/// `slots`, one for each set of fields, and return a mutable reference to them
+///
+/// `slots` is a slice or array of MaybeUninit. Each slot is placed like
+/// [`place!`], in order, with the struct path given once before the list of
+/// fields.
+///
+/// If an initializer panics, the slots already placed and the fields of the
+/// current one already written are dropped, and `slots` is left
+/// uninitialized.
+///
+/// It is your responsibility to drop the placed values if needed when you're
+/// done with them.
+///
+/// # Panics
+///
+/// If there are fewer slots than sets of fields
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_all;
+/// # use std::mem::MaybeUninit;
+///
+/// struct Entry {
+///     id: u32,
+///     name: String,
+/// }
+///
+/// let mut slots = [const { MaybeUninit::uninit() }; 4];
+///
+/// let x: &mut [Entry] = place_all!(
+///     slots,
+///     Entry,
+///     [
+///         { id: 1, name: String::from("one") },
+///         { id: 2, name: String::from("two") },
+///     ]
+/// );
+/// assert_eq!(x.len(), 2);
+/// assert_eq!(x[1].name, "two");
+///
+/// # // SAFETY: The first two slots have been initialized above
+/// # unsafe { std::ptr::drop_in_place(x) };
+/// ```
+///
+/// [`place!`]: crate::place
+#[macro_export]
+macro_rules! place_all {
+    ($slots:expr, $($t:tt)+) => {
+        $crate::__place_all!(@plain $slots, [], $($t)+)
+    };
+}
+
+/// Initialize several slots of the same struct type in-place at the start of
+/// `slots`, and return a mutable reference to them, or an error if there are
+/// too few slots
+///
+/// This works exactly like [`place_all!`], except it returns a
+/// [`PlaceError::TooFewSlots`] instead of panicking. No initializers are
+/// evaluated if there are too few slots.
+///
+/// # Errors
+///
+/// If there are fewer slots than sets of fields
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{try_place_all, PlaceError};
+/// # use std::mem::MaybeUninit;
+///
+/// struct Entry {
+///     id: u32,
+/// }
+///
+/// let mut slots = [const { MaybeUninit::uninit() }; 1];
+///
+/// let x = try_place_all!(slots, Entry, [{ id: 1 }, { id: 2 }]);
+/// assert!(matches!(x, Err(PlaceError::TooFewSlots { needed: 2, got: 1 })));
+/// ```
+///
+/// [`place_all!`]: crate::place_all
+/// [`PlaceError::TooFewSlots`]: crate::PlaceError::TooFewSlots
+#[macro_export]
+macro_rules! try_place_all {
+    ($slots:expr, $($t:tt)+) => {
+        $crate::__place_all!(@try $slots, [], $($t)+)
+    };
+}
+
+/// Collects the path of the type, up until the list of fields
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_all {
+    (@slot $slot:ident, [$($path:tt)+], $fields:tt) => {
+        $crate::place!($slot, $($path)+ $fields)
+    };
+
+    // Each set of fields as `()`, for counting them
+    (@unit $fields:tt) => {
+        ()
+    };
+
+    // Checks there are enough slots before placing them
+    (@check plain $slots:ident, $len:ident, $place:block) => {{
+        $crate::__private::check_slots($slots, $len);
+        $place
+    }};
+
+    (@check try $slots:ident, $len:ident, $place:block) => {
+        match $crate::__private::try_check_slots($slots, $len) {
+            ::core::result::Result::Ok(()) => ::core::result::Result::Ok($place),
+            ::core::result::Result::Err(e) => ::core::result::Result::Err(e),
+        }
+    };
+
+    (@$mode:ident $slots:expr, $path:tt, , [$({ $($fields:tt)* }),* $(,)?] $(,)?) => {{
+        use $crate::__private::AsPlace as _;
+        let slots: &mut [::core::mem::MaybeUninit<_>] = $slots.__as_place();
+        const LEN: usize = <[()]>::len(&[$($crate::__place_all!(@unit { $($fields)* })),*]);
+        $crate::__place_all!(@check $mode slots, LEN, {
+            // Slots are only accessed through this pointer, so the guard can
+            // still drop them. `mut` is unused without any slots.
+            #[allow(unused_mut)]
+            let mut guard = $crate::__private::SliceGuard {
+                ptr: $crate::__private::slice_ptr(slots),
+                init: 0,
+            };
+            $(
+                // SAFETY: There are at least LEN slots
+                #[allow(unused_unsafe)]
+                let slot = unsafe { guard.next() };
+                $crate::__place_all!(@slot slot, $path, { $($fields)* });
+                guard.init += 1;
+            )*
+            let ptr = guard.ptr;
+            ::core::mem::forget(guard);
+            // SAFETY: The first LEN slots have been initialized above
+            #[allow(unused_unsafe)]
+            let init = unsafe { $crate::__private::slice_init(slots, ptr, LEN) };
+            init
+        })
+    }};
+
+    (@$mode:ident $slots:expr, [$($path:tt)*], $next:tt $($rest:tt)+) => {
+        $crate::__place_all!(@$mode $slots, [$($path)* $next], $($rest)+)
+    };
+}
+
+/// Initialize the start of `slots` element by element from an iterator, and
+/// return mutable references to the initialized elements and the rest
+///
+/// `slots` is a slice or array of MaybeUninit. Each item of the iterator is
+/// written to the next slot, until either runs out, so items beyond the
+/// length of `slots` are left in the iterator.
+///
+/// If the iterator panics, the elements already written are dropped, and
+/// `slots` is left uninitialized.
+///
+/// It is your responsibility to drop the initialized elements if needed when
+/// you're done with them.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_slice_from_iter;
+/// # use std::mem::MaybeUninit;
+///
+/// let mut slots = [const { MaybeUninit::uninit() }; 4];
+///
+/// let (x, rest): (&mut [String], &mut [MaybeUninit<String>]) =
+///     place_slice_from_iter!(slots, "a b c".split(' ').map(String::from));
+/// assert_eq!(x, ["a", "b", "c"]);
+/// assert_eq!(rest.len(), 1);
+///
+/// # // SAFETY: x has been initialized above
+/// # unsafe { std::ptr::drop_in_place(x) };
+/// ```
+#[macro_export]
+macro_rules! place_slice_from_iter {
+    ($slots:expr, $iter:expr $(,)?) => {{
+        use $crate::__private::AsPlace as _;
+        let slots: &mut [::core::mem::MaybeUninit<_>] = $slots.__as_place();
+        $crate::__private::place_slice_from_iter(slots, $iter)
+    }};
+}
+
+/// Copy the bytes of the slice `src` into the start of a new `[u8; N]`,
+/// zeroing the rest
+///
+/// This is for fixed size byte array fields filled from a slice, such as
+/// keys or names in protocol structs. `N` is usually inferred from the field,
+/// but may also be given, as `copy_slice!(src, N)`.
+///
+/// # Panics
+///
+/// If `src` is longer than `N`
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{copy_slice, place};
+/// # use std::mem::MaybeUninit;
+///
+/// struct Hello {
+///     version: u8,
+///     name: [u8; 16],
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut Hello = place!(
+///     buf,
+///     Hello {
+///         version: 1,
+///         name: copy_slice!(b"works"),
+///     }
+/// );
+/// assert_eq!(x.name[..6], *b"works\0");
+///
+/// let key = copy_slice!(&[1, 2, 3], 4);
+/// assert_eq!(key, [1, 2, 3, 0]);
+/// ```
+#[macro_export]
+macro_rules! copy_slice {
+    ($src:expr $(,)?) => {
+        $crate::__private::copy_slice($src)
+    };
+
+    ($src:expr, $len:expr $(,)?) => {
+        $crate::__private::copy_slice::<{ $len }>($src)
+    };
+}
+
+/// Drops the first `init` elements of `ptr`, unless forgotten
+pub struct SliceGuard<T> {
+    pub ptr: *mut T,
+    pub init: usize,
+}
+
+impl<T> SliceGuard<T> {
+    /// The element after those initialized
+    ///
+    /// # Safety
+    ///
+    /// It must be in bounds, and not otherwise borrowed
+    pub unsafe fn next<'a>(&self) -> &'a mut MaybeUninit<T> {
+        // SAFETY: Caller ensures the element is in bounds and unborrowed
+        unsafe { &mut *self.ptr.add(self.init).cast() }
+    }
+}
+
+impl<T> Drop for SliceGuard<T> {
+    fn drop(&mut self) {
+        // SAFETY: The first `init` elements have been written
+        unsafe { ptr::slice_from_raw_parts_mut(self.ptr, self.init).drop_in_place() }
+    }
+}
+
+/// Implementation of [`place_array!`]
+pub fn place_array<T, const N: usize>(
+    buf: &mut MaybeUninit<[T; N]>,
+    mut f: impl FnMut(usize) -> T,
+) -> &mut [T; N] {
+    let ptr = buf.as_mut_ptr().cast::<T>();
+    let mut guard = SliceGuard { ptr, init: 0 };
+    for i in 0..N {
+        let val = f(i);
+        // SAFETY: `i` is in bounds of the array
+        unsafe { ptr.add(i).write(val) };
+        guard.init += 1;
+    }
+    core::mem::forget(guard);
+    // SAFETY: All elements have been initialized above
+    unsafe { buf.assume_init_mut() }
+}
+
+/// Implementation of [`place_grid!`]
+pub fn place_grid<T, const N: usize, const M: usize>(
+    buf: &mut MaybeUninit<[[T; M]; N]>,
+    mut f: impl FnMut(usize, usize) -> T,
+) -> &mut [[T; M]; N] {
+    // The inner arrays are contiguous, so all elements can be written, and
+    // dropped, as one slice
+    let ptr = buf.as_mut_ptr().cast::<T>();
+    let mut guard = SliceGuard { ptr, init: 0 };
+    for i in 0..N {
+        for j in 0..M {
+            let val = f(i, j);
+            // SAFETY: `i` and `j` are in bounds of the arrays
+            unsafe { ptr.add(i * M + j).write(val) };
+            guard.init += 1;
+        }
+    }
+    core::mem::forget(guard);
+    // SAFETY: All elements have been initialized above
+    unsafe { buf.assume_init_mut() }
+}
+
+/// Implementation of [`place_each!`]
+pub fn place_each<T, const N: usize>(
+    buf: &mut MaybeUninit<[T; N]>,
+    iter: impl IntoIterator<Item = T>,
+) -> Result<&mut [T; N], usize> {
+    let ptr = buf.as_mut_ptr().cast::<T>();
+    let mut guard = SliceGuard { ptr, init: 0 };
+    // `take` ensures no more than N items are consumed
+    for (i, val) in iter.into_iter().take(N).enumerate() {
+        // SAFETY: `i` is in bounds of the array
+        unsafe { ptr.add(i).write(val) };
+        guard.init += 1;
+    }
+    if guard.init < N {
+        return Err(guard.init);
+    }
+    core::mem::forget(guard);
+    // SAFETY: All elements have been initialized above
+    Ok(unsafe { buf.assume_init_mut() })
+}
+
+/// Implementation of [`place_slice_from_iter!`]
+///
+/// [`place_slice_from_iter!`]: crate::place_slice_from_iter
+pub fn place_slice_from_iter<T>(
+    slots: &mut [MaybeUninit<T>],
+    iter: impl IntoIterator<Item = T>,
+) -> (&mut [T], &mut [MaybeUninit<T>]) {
+    let ptr = slice_ptr(slots);
+    let mut guard = SliceGuard { ptr, init: 0 };
+    // `take` ensures no more than `slots.len()` items are consumed
+    for (i, val) in iter.into_iter().take(slots.len()).enumerate() {
+        // SAFETY: `i` is in bounds of slots
+        unsafe { ptr.add(i).write(val) };
+        guard.init += 1;
+    }
+    let len = guard.init;
+    core::mem::forget(guard);
+    let (init, rest) = slots.split_at_mut(len);
+    // SAFETY: The first len elements have been initialized above
+    let init = unsafe { &mut *(init as *mut [MaybeUninit<T>] as *mut [T]) };
+    (init, rest)
+}
+
+/// Implementation of [`copy_slice!`]
+#[track_caller]
+pub fn copy_slice<const N: usize>(src: &[u8]) -> [u8; N] {
+    assert!(
+        src.len() <= N,
+        "{} bytes are too many for [u8; {N}]",
+        src.len(),
+    );
+    let mut bytes = [0; N];
+    bytes[..src.len()].copy_from_slice(src);
+    bytes
+}
+
+/// Check there are at least `len` slots
+///
+/// # Panics
+///
+/// If there aren't
+#[track_caller]
+pub fn check_slots<T>(slots: &[T], len: usize) {
+    if let Err(e) = try_check_slots(slots, len) {
+        panic!("{e}");
+    }
+}
+
+/// Check there are at least `len` slots
+///
+/// # Errors
+///
+/// If there aren't
+pub fn try_check_slots<T>(slots: &[T], len: usize) -> Result<(), PlaceError> {
+    if slots.len() < len {
+        return Err(PlaceError::TooFewSlots {
+            needed: len,
+            got: slots.len(),
+        });
+    }
+    Ok(())
+}
+
+/// The pointer to the elements of `slots`
+pub fn slice_ptr<T>(slots: &mut [MaybeUninit<T>]) -> *mut T {
+    slots.as_mut_ptr().cast()
+}
+
+/// Treat the first `len` elements of `slots`, at `ptr`, as initialized
+///
+/// # Safety
+///
+/// `ptr` must be from [`slice_ptr`] for `slots`, and the first `len` elements
+/// initialized
+pub unsafe fn slice_init<T>(slots: &mut [MaybeUninit<T>], ptr: *mut T, len: usize) -> &mut [T] {
+    let _ = slots;
+    // SAFETY: Caller ensures the first len elements at ptr are initialized,
+    // which is borrowed from slots
+    unsafe { &mut *ptr::slice_from_raw_parts_mut(ptr, len) }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{explode, DropCount},
+        PlaceError,
+    };
+    use core::{
+        mem::MaybeUninit,
+        ptr,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    #[test]
+    fn array() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut [String; 4] = place_array!(buf, |i| i.to_string());
+        assert_eq!(x, &["0", "1", "2", "3"]);
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+
+        // Through a reference
+        let slot = &mut buf;
+        let x: &mut [String; 4] = place_array!(slot, |i| (i * 2).to_string());
+        assert_eq!(x, &["0", "2", "4", "6"]);
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    #[test]
+    fn array_panic() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = MaybeUninit::uninit();
+
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            let _: &mut [(String, DropCount); 4] = place_array!(buf, |i| {
+                if i == 2 {
+                    explode()
+                } else {
+                    (i.to_string(), DropCount(&drops))
+                }
+            });
+        }));
+        assert!(res.is_err());
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn grid() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut [[String; 2]; 2] = place_grid!(buf, |i, j| format!("{i}{j}"));
+        assert_eq!(x, &[["00", "01"], ["10", "11"]]);
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+
+        // Through a reference
+        let slot = &mut buf;
+        let x: &mut [[String; 2]; 2] = place_grid!(slot, |i, j| format!("{j}{i}"));
+        assert_eq!(x, &[["00", "10"], ["01", "11"]]);
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    #[test]
+    fn grid_panic() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = MaybeUninit::uninit();
+
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            let _: &mut [[DropCount; 2]; 2] = place_grid!(buf, |i, j| {
+                if (i, j) == (1, 1) {
+                    explode()
+                } else {
+                    DropCount(&drops)
+                }
+            });
+        }));
+        assert!(res.is_err());
+        // Including the element of the first row
+        assert_eq!(drops.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn each() {
+        let mut buf = MaybeUninit::uninit();
+        let mut iter = (0..6).map(|i| i.to_string());
+
+        let x: &mut [String; 4] = place_each!(buf, &mut iter).unwrap();
+        assert_eq!(x, &["0", "1", "2", "3"]);
+        assert_eq!(iter.next().as_deref(), Some("4"));
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    #[test]
+    fn each_too_few() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = MaybeUninit::<[DropCount; 4]>::uninit();
+
+        let res = place_each!(buf, (0..3).map(|_| DropCount(&drops)));
+        assert_eq!(res.unwrap_err(), 3);
+        assert_eq!(drops.load(Ordering::Relaxed), 3);
+    }
+
+    struct Slot<'a> {
+        id: u32,
+        count: DropCount<'a>,
+    }
+
+    #[test]
+    fn all() {
+        let drops = AtomicUsize::new(0);
+        let mut slots = [const { MaybeUninit::uninit() }; 3];
+
+        let x: &mut [Slot] = place_all!(
+            slots,
+            Slot,
+            [
+                {
+                    id: 1,
+                    count: DropCount(&drops),
+                },
+                {
+                    id: 2,
+                    count: DropCount(&drops),
+                },
+            ]
+        );
+        assert_eq!(x.len(), 2);
+        assert_eq!(x[1].id, 2);
+
+        // SAFETY: The slots have been initialized above
+        unsafe { ptr::drop_in_place(x) };
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+
+        let x: &mut [Slot] = place_all!(slots, Slot, []);
+        assert!(x.is_empty());
+    }
+
+    #[test]
+    fn all_panic() {
+        let drops = AtomicUsize::new(0);
+        let mut slots = [const { MaybeUninit::uninit() }; 3];
+
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            let _: &mut [Slot] = place_all!(
+                slots,
+                Slot,
+                [
+                    {
+                        id: 1,
+                        count: DropCount(&drops),
+                    },
+                    {
+                        count: DropCount(&drops),
+                        id: explode(),
+                    },
+                    {
+                        id: 3,
+                        count: DropCount(&drops),
+                    },
+                ]
+            );
+        }));
+        assert!(res.is_err());
+        // The first slot, and the field of the second
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    #[should_panic = "too few"]
+    fn all_too_few() {
+        let drops = AtomicUsize::new(0);
+        let mut slots = [const { MaybeUninit::uninit() }; 1];
+
+        let _: &mut [Slot] = place_all!(
+            slots,
+            Slot,
+            [
+                {
+                    id: 1,
+                    count: DropCount(&drops),
+                },
+                {
+                    id: 2,
+                    count: DropCount(&drops),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn try_all() {
+        let drops = AtomicUsize::new(0);
+        let mut slots = [const { MaybeUninit::uninit() }; 1];
+
+        let x = try_place_all!(
+            slots,
+            Slot,
+            [{
+                id: 1,
+                count: DropCount(&drops),
+            }]
+        );
+        let x: &mut [Slot] = x.unwrap();
+        assert_eq!(x[0].id, 1);
+        // SAFETY: The slot has been initialized above
+        unsafe { ptr::drop_in_place(x) };
+
+        // Nothing is evaluated when there are too few slots
+        let x = try_place_all!(
+            slots,
+            Slot,
+            [
+                {
+                    id: 1,
+                    count: explode(),
+                },
+                {
+                    id: 2,
+                    count: explode(),
+                },
+            ]
+        );
+        assert_eq!(x.err(), Some(PlaceError::TooFewSlots { needed: 2, got: 1 }));
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn from_iter() {
+        let drops = AtomicUsize::new(0);
+        let mut slots = [const { MaybeUninit::uninit() }; 3];
+
+        let mut iter = (0..2).map(|_| DropCount(&drops));
+        let (x, rest) = place_slice_from_iter!(slots, &mut iter);
+        assert_eq!(x.len(), 2);
+        assert_eq!(rest.len(), 1);
+
+        // SAFETY: x has been initialized above
+        unsafe { ptr::drop_in_place(x) };
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+
+        // Items beyond the slots are left in the iterator
+        let mut slots = [const { MaybeUninit::uninit() }; 3];
+        let mut iter = 0..5;
+        let (x, rest): (&mut [u32], _) = place_slice_from_iter!(slots[1..], &mut iter);
+        assert_eq!(x, [0, 1]);
+        assert!(rest.is_empty());
+        assert_eq!(iter.next(), Some(2));
+    }
+
+    #[test]
+    fn from_iter_panic() {
+        let drops = AtomicUsize::new(0);
+        let mut slots = [const { MaybeUninit::uninit() }; 4];
+
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            let iter = (0..3).map(|i| if i == 2 { explode() } else { DropCount(&drops) });
+            let _ = place_slice_from_iter!(slots, iter);
+        }));
+        assert!(res.is_err());
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn copy() {
+        let x: [u8; 4] = copy_slice!(&[1, 2, 3, 4]);
+        assert_eq!(x, [1, 2, 3, 4]);
+
+        let x = copy_slice!(b"ab", 4);
+        assert_eq!(x, [b'a', b'b', 0, 0]);
+
+        let x: [u8; 0] = copy_slice!(&[]);
+        assert_eq!(x, []);
+    }
+
+    #[test]
+    #[should_panic = "5 bytes are too many for [u8; 4]"]
+    fn copy_too_long() {
+        let _ = copy_slice!(&[1, 2, 3, 4, 5], 4);
+    }
+}
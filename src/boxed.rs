@@ -0,0 +1,445 @@
+//! Placing into a new [`Box`](alloc::boxed::Box)
+
+use crate::{Place, PlaceError};
+use alloc::boxed::Box;
+use core::{
+    alloc::Layout,
+    mem::{self, MaybeUninit},
+    ops::{Deref, DerefMut},
+};
+
+/// Initialize a struct in-place in a new [`Box`], and return it
+///
+/// This works exactly like [`place!`], except the buffer is allocated with
+/// [`Box::new_uninit`], which is stable since Rust 1.82. If a field
+/// initializer panics the written fields are dropped, and the allocation is
+/// freed.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_box;
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// let b: Box<MyCoolStruct> = place_box!(MyCoolStruct {
+///     b: true,
+///     s: String::from("works"),
+/// });
+/// ```
+///
+/// As this is an expression, it can also be used as the value of a field,
+/// such as for the children of a tree, which are then each placed directly
+/// into their own allocation. If a later field initializer panics, the boxes
+/// already written are dropped along with the other fields.
+///
+/// ```rust
+/// # use place::{place, place_box};
+/// # use std::mem::MaybeUninit;
+///
+/// struct Node {
+///     value: u32,
+///     left: Option<Box<Node>>,
+///     right: Option<Box<Node>>,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut Node = place!(
+///     buf,
+///     Node {
+///         value: 1,
+///         left: Some(place_box!(Node {
+///             value: 2,
+///             left: None,
+///             right: None,
+///         })),
+///         right: None,
+///     }
+/// );
+/// assert_eq!(x.left.as_ref().unwrap().value, 2);
+///
+/// # // SAFETY: buf has been initialized above
+/// # unsafe { buf.assume_init_drop() };
+/// ```
+///
+/// Only `alloc` is needed, so this works in `no_std` crates too
+///
+/// ```rust
+/// #![no_std]
+/// # // Doctests still need std to run
+/// # extern crate std;
+/// extern crate alloc;
+///
+/// use alloc::{boxed::Box, string::String};
+/// use place::place_box;
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// pub fn make() -> Box<MyCoolStruct> {
+///     place_box!(MyCoolStruct {
+///         b: true,
+///         s: String::from("works"),
+///     })
+/// }
+/// # fn main() {
+/// #     assert!(make().b);
+/// # }
+/// ```
+///
+/// [`Box`]: alloc::boxed::Box
+/// [`Box::new_uninit`]: alloc::boxed::Box::new_uninit
+/// [`place!`]: crate::place
+#[macro_export]
+macro_rules! place_box {
+    ($($t:tt)+) => {{
+        let mut buf = $crate::__private::RawBox::new($crate::__private::Box::new_uninit());
+        $crate::place!(buf, $($t)+);
+        // SAFETY: buf has been initialized above
+        #[allow(unused_unsafe)]
+        let init = unsafe { buf.assume_init() };
+        init
+    }};
+}
+
+/// Initialize a struct in-place in a new [`Box`], returning an error if
+/// allocating it fails
+///
+/// This works exactly like [`place_box!`], except allocation failure returns
+/// [`PlaceError::AllocFailed`] instead of aborting. As `Box::try_new_uninit`
+/// is unstable, the buffer is allocated by the global allocator directly.
+///
+/// # Errors
+///
+/// If allocating the buffer fails
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{try_place_box, PlaceError};
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// let b: Result<Box<MyCoolStruct>, PlaceError> = try_place_box!(MyCoolStruct {
+///     b: true,
+///     s: String::from("works"),
+/// });
+/// assert!(b.unwrap().b);
+/// ```
+///
+/// [`Box`]: alloc::boxed::Box
+/// [`PlaceError::AllocFailed`]: crate::PlaceError::AllocFailed
+/// [`place_box!`]: crate::place_box
+#[macro_export]
+macro_rules! try_place_box {
+    ($($t:tt)+) => {
+        match $crate::__private::try_box_uninit() {
+            ::core::result::Result::Ok(buf) => {
+                let mut buf = $crate::__private::RawBox::new(buf);
+                $crate::place!(buf, $($t)+);
+                // SAFETY: buf has been initialized above
+                #[allow(unused_unsafe)]
+                let init = unsafe { buf.assume_init() };
+                ::core::result::Result::Ok(init)
+            }
+            ::core::result::Result::Err(e) => ::core::result::Result::Err(e),
+        }
+    };
+}
+
+/// Initialize a struct in-place in a new [`Box`], and return it pinned
+///
+/// This works exactly like [`place_box!`], and the value is never moved, so
+/// it may refer to its own fields, such as through [`field!`]. The box is
+/// only written through the pointer those are derived from, so they stay
+/// valid so long as the struct is `!Unpin`, such as with [`PhantomPinned`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{field, place_pin_box};
+/// # use std::{marker::PhantomPinned, pin::Pin};
+///
+/// struct SelfRef {
+///     data: [u8; 16],
+///     first: *const u8,
+///     _pin: PhantomPinned,
+/// }
+///
+/// let x: Pin<Box<SelfRef>> = place_pin_box!(SelfRef {
+///     data: [1; 16],
+///     first: field!(data).cast(),
+///     _pin: PhantomPinned,
+/// });
+/// // SAFETY: first points to data, which has been initialized
+/// assert_eq!(unsafe { *x.first }, 1);
+/// ```
+///
+/// [`Box`]: alloc::boxed::Box
+/// [`field!`]: crate::field
+/// [`place_box!`]: crate::place_box
+/// [`PhantomPinned`]: core::marker::PhantomPinned
+#[macro_export]
+macro_rules! place_pin_box {
+    ($($t:tt)+) => {
+        $crate::__private::Box::into_pin($crate::place_box!($($t)+))
+    };
+}
+
+/// A [`Box`] that's only accessed through its raw pointer while placing
+///
+/// Moving or reborrowing a `Box` retags it under Stacked Borrows, which would
+/// invalidate the pointers from `field!` stored in the value, so the box is
+/// turned into a raw pointer for as long as it's being placed into. If
+/// placing panics, the allocation is freed.
+///
+/// [`Box`]: alloc::boxed::Box
+pub struct RawBox<T>(*mut MaybeUninit<T>);
+
+impl<T> RawBox<T> {
+    pub fn new(buf: Box<MaybeUninit<T>>) -> Self {
+        Self(Box::into_raw(buf))
+    }
+
+    /// # Safety
+    ///
+    /// The value must have been initialized
+    pub unsafe fn assume_init(self) -> Box<T> {
+        let ptr = self.0.cast::<T>();
+        mem::forget(self);
+        // SAFETY: ptr is from Box::into_raw, and the caller ensures it's
+        // initialized
+        unsafe { Box::from_raw(ptr) }
+    }
+}
+
+impl<T> Drop for RawBox<T> {
+    fn drop(&mut self) {
+        // SAFETY: self.0 is from Box::into_raw, and MaybeUninit drops nothing
+        drop(unsafe { Box::from_raw(self.0) });
+    }
+}
+
+impl<T> Deref for RawBox<T> {
+    type Target = MaybeUninit<T>;
+
+    fn deref(&self) -> &MaybeUninit<T> {
+        // SAFETY: self.0 is from Box::into_raw
+        unsafe { &*self.0 }
+    }
+}
+
+impl<T> DerefMut for RawBox<T> {
+    fn deref_mut(&mut self) -> &mut MaybeUninit<T> {
+        // SAFETY: self.0 is from Box::into_raw
+        unsafe { &mut *self.0 }
+    }
+}
+
+// SAFETY: self.0 is from Box::into_raw, and only freed when dropped
+unsafe impl<T> Place<T> for RawBox<T> {
+    fn as_uninit_ptr(&mut self) -> *mut T {
+        self.0.cast()
+    }
+
+    unsafe fn finish(&mut self) -> &mut T {
+        // SAFETY: Caller ensures we're initialized
+        unsafe { &mut *self.0.cast() }
+    }
+}
+
+/// Allocate a [`Box`] for `T`, returning an error if allocation fails
+///
+/// [`Box`]: alloc::boxed::Box
+pub fn try_box_uninit<T>() -> Result<Box<MaybeUninit<T>>, PlaceError> {
+    let layout = Layout::new::<T>();
+    if layout.size() == 0 {
+        // Nothing is allocated
+        return Ok(Box::new_uninit());
+    }
+    // SAFETY: layout isn't zero sized
+    let ptr = unsafe { alloc::alloc::alloc(layout) }.cast::<MaybeUninit<T>>();
+    if ptr.is_null() {
+        return Err(PlaceError::AllocFailed { layout });
+    }
+    // SAFETY: ptr was allocated by the global allocator with the layout of
+    // MaybeUninit<T>, as Box does, and needn't be initialized
+    Ok(unsafe { Box::from_raw(ptr) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{explode, DropCount};
+    use alloc::string::String;
+    use core::{
+        marker::PhantomPinned,
+        pin::Pin,
+        ptr,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    #[derive(Debug, PartialEq)]
+    struct MyCoolStruct {
+        b: bool,
+        s: String,
+    }
+
+    #[test]
+    fn boxed() {
+        let b: Box<MyCoolStruct> = place_box!(MyCoolStruct {
+            b: true,
+            s: String::from("works"),
+        });
+        assert_eq!(
+            *b,
+            MyCoolStruct {
+                b: true,
+                s: String::from("works")
+            }
+        );
+    }
+
+    #[test]
+    fn boxed_panic() {
+        let res = catch_unwind(|| {
+            let _: Box<MyCoolStruct> = place_box!(MyCoolStruct {
+                s: String::from("leaks?"),
+                b: explode(),
+            });
+        });
+        assert!(res.is_err());
+    }
+
+    struct Pinned {
+        value: String,
+        this: *const String,
+        _pin: PhantomPinned,
+    }
+
+    #[test]
+    fn pin_box() {
+        let x: Pin<Box<Pinned>> = place_pin_box!(Pinned {
+            value: String::from("works"),
+            this: crate::field!(value),
+            _pin: PhantomPinned,
+        });
+        assert!(ptr::eq(x.this, &x.value));
+        // SAFETY: this points to value
+        assert_eq!(unsafe { &*x.this }, "works");
+    }
+
+    struct Node<'a> {
+        count: DropCount<'a>,
+        left: Option<Box<Node<'a>>>,
+        right: Option<Box<Node<'a>>>,
+    }
+
+    fn leaf(drops: &AtomicUsize) -> Box<Node<'_>> {
+        place_box!(Node {
+            count: DropCount(drops),
+            left: None,
+            right: None,
+        })
+    }
+
+    #[test]
+    fn tree() {
+        let drops = AtomicUsize::new(0);
+
+        let x: Box<Node> = place_box!(Node {
+            count: DropCount(&drops),
+            left: Some(place_box!(Node {
+                count: DropCount(&drops),
+                left: Some(leaf(&drops)),
+                right: None,
+            })),
+            right: Some(leaf(&drops)),
+        });
+        let left = x.left.as_ref().unwrap();
+        assert!(left.left.is_some());
+        assert!(left.right.is_none());
+        assert!(x.right.is_some());
+
+        drop(x);
+        assert_eq!(drops.load(Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn tree_panic() {
+        let drops = AtomicUsize::new(0);
+
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            let _: Box<Node> = place_box!(Node {
+                count: DropCount(&drops),
+                left: Some(place_box!(Node {
+                    count: DropCount(&drops),
+                    left: Some(leaf(&drops)),
+                    right: None,
+                })),
+                right: explode(),
+            });
+        }));
+        assert!(res.is_err());
+        // Every node, and box, already placed was dropped
+        assert_eq!(drops.load(Ordering::Relaxed), 3);
+
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            let _: Box<Node> = place_box!(Node {
+                count: DropCount(&drops),
+                left: Some(place_box!(Node {
+                    count: DropCount(&drops),
+                    left: Some(leaf(&drops)),
+                    right: explode(),
+                })),
+                right: None,
+            });
+        }));
+        assert!(res.is_err());
+        assert_eq!(drops.load(Ordering::Relaxed), 6);
+    }
+
+    #[test]
+    fn try_boxed() {
+        let b: Result<Box<MyCoolStruct>, _> = try_place_box!(MyCoolStruct {
+            b: true,
+            s: String::from("works"),
+        });
+        assert_eq!(b.unwrap().s, "works");
+
+        // Zero sized types aren't allocated
+        let b: Result<Box<Unit>, _> = try_place_box!(Unit);
+        assert!(b.is_ok());
+    }
+
+    struct Unit;
+
+    struct Huge {
+        // Larger than any address space
+        data: [u8; 1 << 60],
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn try_boxed_fail() {
+        let b: Result<Box<Huge>, _> = try_place_box!(Huge {
+            data: emplace_fn!(|buf| crate::place_array!(*buf, |_| unreachable!())),
+        });
+        assert_eq!(
+            b.err(),
+            Some(PlaceError::AllocFailed {
+                layout: Layout::new::<Huge>()
+            })
+        );
+    }
+}
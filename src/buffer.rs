@@ -0,0 +1,239 @@
+//! Buffers that can be placed into
+
+use core::mem::MaybeUninit;
+
+/// A buffer that a `T` can be placed into
+///
+/// This is what [`place!`](crate::place) uses to access `buf`, and can be
+/// implemented for your own buffer types, such as handles to an arena or slab.
+///
+/// # Safety
+///
+/// [`Place::as_uninit_ptr`] must return a pointer that is non-null, properly
+/// aligned, and valid for reads and writes of `T`, which nothing else accesses
+/// while the buffer is borrowed. It must return the same pointer every time, so
+/// long as the buffer is not moved or otherwise modified.
+///
+/// [`Place::finish`] must return a reference to the value at that same
+/// pointer. `place!` and the other macros don't call it, and derive the
+/// reference they return from the pointer instead, so that pointers to the
+/// fields taken while placing, such as with [`field!`](crate::field), stay
+/// valid.
+pub unsafe trait Place<T> {
+    /// Pointer to the, possibly uninitialized, `T` in the buffer
+    fn as_uninit_ptr(&mut self) -> *mut T;
+
+    /// Reference to the now initialized `T` in the buffer
+    ///
+    /// # Safety
+    ///
+    /// The `T` at [`Place::as_uninit_ptr`] must have been initialized
+    unsafe fn finish(&mut self) -> &mut T;
+}
+
+// SAFETY: as_mut_ptr is valid, and finish refers to the same memory
+unsafe impl<T> Place<T> for MaybeUninit<T> {
+    fn as_uninit_ptr(&mut self) -> *mut T {
+        self.as_mut_ptr()
+    }
+
+    unsafe fn finish(&mut self) -> &mut T {
+        // SAFETY: Caller ensures we're initialized
+        unsafe { self.assume_init_mut() }
+    }
+}
+
+// SAFETY: Forwards to the MaybeUninit implementation
+unsafe impl<T> Place<T> for &mut MaybeUninit<T> {
+    fn as_uninit_ptr(&mut self) -> *mut T {
+        self.as_mut_ptr()
+    }
+
+    unsafe fn finish(&mut self) -> &mut T {
+        // SAFETY: Caller ensures we're initialized
+        unsafe { self.assume_init_mut() }
+    }
+}
+
+// SAFETY: Forwards to the MaybeUninit implementation
+#[cfg(feature = "alloc")]
+unsafe impl<T> Place<T> for alloc::boxed::Box<MaybeUninit<T>> {
+    fn as_uninit_ptr(&mut self) -> *mut T {
+        self.as_mut_ptr()
+    }
+
+    unsafe fn finish(&mut self) -> &mut T {
+        // SAFETY: Caller ensures we're initialized
+        unsafe { self.assume_init_mut() }
+    }
+}
+
+/// Move `value` into `buf`, and return a mutable reference to it
+///
+/// This is the same as [`MaybeUninit::write`], for any [`Place`]. Unlike
+/// [`place!`], `value` is built before being moved in, rather than in-place,
+/// but nothing else is required.
+///
+/// Neither needs `unsafe` in your crate, so both can be used with
+/// `#![forbid(unsafe_code)]`, which doesn't apply to the `unsafe` in
+/// macros from other crates. Only the macros documented as needing an
+/// `unsafe` block, such as [`place_ptr!`], can't be.
+///
+/// # Examples
+///
+/// ```rust
+/// #![forbid(unsafe_code)]
+/// # use place::{place, place_into};
+/// # use std::mem::MaybeUninit;
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+/// let x: &mut MyCoolStruct = place_into(
+///     &mut buf,
+///     MyCoolStruct {
+///         b: true,
+///         s: String::from("works"),
+///     },
+/// );
+/// assert_eq!(x.s, "works");
+///
+/// let mut buf = MaybeUninit::uninit();
+/// let x: &mut MyCoolStruct = place!(
+///     buf,
+///     MyCoolStruct {
+///         b: true,
+///         s: String::from("works"),
+///     }
+/// );
+/// assert_eq!(x.s, "works");
+/// ```
+///
+/// [`place!`]: crate::place
+/// [`place_ptr!`]: crate::place_ptr
+pub fn place_into<T, P: Place<T> + ?Sized>(buf: &mut P, value: T) -> &mut T {
+    // SAFETY: Place ensures the pointer is valid for writes
+    unsafe { buf.as_uninit_ptr().write(value) };
+    // SAFETY: Initialized above
+    unsafe { buf.finish() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::place;
+
+    #[derive(Debug, PartialEq)]
+    struct MyCoolStruct {
+        b: bool,
+        s: String,
+    }
+
+    /// A fixed size arena, handing out slots by index
+    struct Arena {
+        slots: [MaybeUninit<MyCoolStruct>; 2],
+    }
+
+    struct Slot<'a> {
+        arena: &'a mut Arena,
+        idx: usize,
+    }
+
+    // SAFETY: Slots are valid MaybeUninit, and finish refers to the same one
+    unsafe impl Place<MyCoolStruct> for Slot<'_> {
+        fn as_uninit_ptr(&mut self) -> *mut MyCoolStruct {
+            self.arena.slots[self.idx].as_mut_ptr()
+        }
+
+        unsafe fn finish(&mut self) -> &mut MyCoolStruct {
+            // SAFETY: Caller ensures we're initialized
+            unsafe { self.arena.slots[self.idx].assume_init_mut() }
+        }
+    }
+
+    #[test]
+    fn custom_place() {
+        let mut arena = Arena {
+            slots: [const { MaybeUninit::uninit() }; 2],
+        };
+        let mut slot = Slot {
+            arena: &mut arena,
+            idx: 1,
+        };
+
+        let x: &mut MyCoolStruct = place!(
+            slot,
+            MyCoolStruct {
+                b: true,
+                s: String::from("works"),
+            }
+        );
+        assert_eq!(x.s, "works");
+
+        // SAFETY: slot 1 has been initialized above
+        unsafe { arena.slots[1].assume_init_drop() };
+    }
+
+    #[test]
+    fn ref_place() {
+        let mut buf = MaybeUninit::uninit();
+        let buf_ref = &mut buf;
+
+        let x: &mut MyCoolStruct = place!(
+            buf_ref,
+            MyCoolStruct {
+                b: true,
+                s: String::from("works"),
+            }
+        );
+        assert_eq!(x.s, "works");
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn box_place() {
+        let mut buf = alloc::boxed::Box::new_uninit();
+
+        let x: &mut MyCoolStruct = place!(
+            buf,
+            MyCoolStruct {
+                b: true,
+                s: String::from("works"),
+            }
+        );
+        assert_eq!(x.s, "works");
+
+        // SAFETY: buf has been initialized above
+        let buf = unsafe { buf.assume_init() };
+        assert!(buf.b);
+    }
+
+    #[test]
+    fn into() {
+        let mut arena = Arena {
+            slots: [const { MaybeUninit::uninit() }; 2],
+        };
+        let mut slot = Slot {
+            arena: &mut arena,
+            idx: 0,
+        };
+
+        let x = place_into(
+            &mut slot,
+            MyCoolStruct {
+                b: true,
+                s: String::from("works"),
+            },
+        );
+        assert_eq!(x.s, "works");
+
+        // SAFETY: slot 0 has been initialized above
+        unsafe { arena.slots[0].assume_init_drop() };
+    }
+}
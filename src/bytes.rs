@@ -0,0 +1,543 @@
+//! Placing into byte buffers
+
+use crate::PlaceError;
+use core::mem::{align_of, size_of, MaybeUninit};
+
+/// The size and alignment of `T`
+pub const fn size_and_align<T>() -> (usize, usize) {
+    (size_of::<T>(), align_of::<T>())
+}
+
+/// Check that `T` fits at the start of `bytes`
+///
+/// # Errors
+///
+/// If `bytes` is shorter than the size of `T`, or its start isn't aligned for
+/// it
+pub fn check_fit<T>(bytes: &[u8]) -> Result<(), PlaceError> {
+    let (size, align) = size_and_align::<T>();
+    if bytes.len() < size {
+        return Err(PlaceError::TooSmall {
+            needed: size,
+            got: bytes.len(),
+        });
+    }
+    let ptr = bytes.as_ptr();
+    if !ptr.cast::<T>().is_aligned() {
+        return Err(PlaceError::Misaligned {
+            required: align,
+            addr: ptr as usize,
+        });
+    }
+    Ok(())
+}
+
+/// Whether the type `T` fits at the start of the byte slice `bytes`
+///
+/// See [`check_fit`] for why it may not.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::fits;
+///
+/// #[repr(C, align(4))]
+/// struct Region([u8; 16]);
+///
+/// let region = Region([0; 16]);
+///
+/// assert!(fits!(&region.0, u32));
+/// assert!(!fits!(&region.0[1..], u32));
+/// assert!(!fits!(&region.0[12..], u64));
+/// ```
+#[macro_export]
+macro_rules! fits {
+    ($bytes:expr, $typ:ty $(,)?) => {
+        $crate::check_fit::<$typ>($bytes).is_ok()
+    };
+}
+
+/// Initialize a struct in-place at the start of the byte slice `bytes`, and
+/// return a mutable reference
+///
+/// This works exactly like [`place!`], after checking that `bytes` is large
+/// enough for, and aligned to, the type being placed. Like [`place_pin!`],
+/// `bytes` is given as an `unsafe` block, and field initializers aren't
+/// evaluated in it.
+///
+/// # Panics
+///
+/// If `bytes` is shorter than the size of the type, or its start isn't
+/// aligned for it
+///
+/// # Safety
+///
+/// The bytes the value was written to may include uninitialized padding, and
+/// must not be read as `u8` until they've been overwritten, unless the type
+/// has no padding.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_bytes;
+///
+/// #[repr(C)]
+/// struct Header {
+///     magic: u32,
+///     len: u32,
+/// }
+///
+/// #[repr(C, align(4))]
+/// struct Region([u8; 16]);
+///
+/// let mut region = Region([0; 16]);
+///
+/// let x: &mut Header = place_bytes!(
+///     // SAFETY: Header has no padding
+///     unsafe { &mut region.0 },
+///     Header {
+///         magic: 0xCAFE,
+///         len: 8,
+///     }
+/// );
+/// assert_eq!(x.magic, 0xCAFE);
+/// ```
+///
+/// [`place!`]: crate::place
+/// [`place_pin!`]: crate::place_pin
+#[macro_export]
+macro_rules! place_bytes {
+    ($unsafe:tt $bytes:tt, $($t:tt)+) => {{
+        $crate::__place_unsafe!($unsafe);
+        #[allow(unused_unsafe)]
+        let bytes: &mut [u8] = $unsafe $bytes;
+        // SAFETY: The caller's `unsafe` block ensures the bytes aren't read
+        // while uninitialized
+        #[allow(unused_unsafe)]
+        let buf = unsafe { $crate::__private::bytes_uninit(bytes) };
+        $crate::place!(buf, $($t)+)
+    }};
+}
+
+/// Initialize a struct in-place in the uninitialized byte slice `bytes`, and
+/// return a mutable reference along with the bytes after it
+///
+/// The value is placed at the first offset in `bytes` aligned for it, like
+/// with [`place!`], and the remaining bytes after it are returned, so they can
+/// be used for further values, like a bump allocator.
+///
+/// # Panics
+///
+/// If the value doesn't fit in `bytes` once aligned
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_bump;
+/// # use std::mem::MaybeUninit;
+///
+/// struct Header {
+///     len: u16,
+/// }
+///
+/// struct Entry {
+///     id: u64,
+///     value: u32,
+/// }
+///
+/// let mut storage = [MaybeUninit::uninit(); 64];
+///
+/// let (header, rest) = place_bump!(&mut storage, Header { len: 1 });
+/// let (entry, rest) = place_bump!(rest, Entry { id: 1, value: 2 });
+/// assert_eq!(header.len, 1);
+/// assert_eq!(entry.value, 2);
+/// assert!(rest.len() < 64 - 16);
+/// ```
+///
+/// [`place!`]: crate::place
+#[macro_export]
+macro_rules! place_bump {
+    ($bytes:expr, $($t:tt)+) => {{
+        let (buf, rest) = $crate::__private::bump_uninit($bytes);
+        ($crate::place!(buf, $($t)+), rest)
+    }};
+}
+
+/// Initialize a struct in-place in the uninitialized byte slice `bytes`, and
+/// return a mutable reference along with the bytes after it, or an error if it
+/// doesn't fit
+///
+/// This works exactly like [`place_bump!`], except it returns a
+/// [`PlaceError::TooSmall`] instead of panicking, with the number of bytes
+/// left once aligned. No initializers are evaluated if it doesn't fit.
+///
+/// # Errors
+///
+/// If the value doesn't fit in `bytes` once aligned
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{try_place_bump, PlaceError};
+/// # use std::mem::MaybeUninit;
+///
+/// struct Entry {
+///     id: u64,
+/// }
+///
+/// let mut storage = [MaybeUninit::uninit(); 4];
+///
+/// let x = try_place_bump!(&mut storage, Entry { id: 1 });
+/// assert!(matches!(x, Err(PlaceError::TooSmall { needed: 8, .. })));
+/// ```
+///
+/// [`place_bump!`]: crate::place_bump
+/// [`PlaceError::TooSmall`]: crate::PlaceError::TooSmall
+#[macro_export]
+macro_rules! try_place_bump {
+    ($bytes:expr, $($t:tt)+) => {
+        match $crate::__private::try_bump_uninit($bytes) {
+            ::core::result::Result::Ok((buf, rest)) => {
+                ::core::result::Result::Ok(($crate::place!(buf, $($t)+), rest))
+            }
+            ::core::result::Result::Err(e) => ::core::result::Result::Err(e),
+        }
+    };
+}
+
+/// Initialize a struct in-place at the start of the byte slice `bytes`, and
+/// return a mutable reference, or an error if it doesn't fit
+///
+/// This works exactly like [`place_bytes!`], except it returns a
+/// [`PlaceError`] instead of panicking. No initializers are evaluated if it
+/// doesn't fit.
+///
+/// # Errors
+///
+/// If `bytes` is shorter than the size of the type, or its start isn't
+/// aligned for it
+///
+/// # Safety
+///
+/// See [`place_bytes!`]
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{place_bytes_checked, PlaceError};
+///
+/// struct Header {
+///     magic: u32,
+///     len: u32,
+/// }
+///
+/// #[repr(C, align(4))]
+/// struct Region([u8; 16]);
+///
+/// let mut region = Region([0; 16]);
+///
+/// let x = place_bytes_checked!(
+///     // SAFETY: Header has no padding
+///     unsafe { &mut region.0[12..] },
+///     Header { magic: 0xCAFE, len: 8 }
+/// );
+/// assert!(matches!(x, Err(PlaceError::TooSmall { needed: 8, got: 4 })));
+/// ```
+///
+/// [`place_bytes!`]: crate::place_bytes
+#[macro_export]
+macro_rules! place_bytes_checked {
+    ($unsafe:tt $bytes:tt, $($t:tt)+) => {{
+        $crate::__place_unsafe!($unsafe);
+        #[allow(unused_unsafe)]
+        let bytes: &mut [u8] = $unsafe $bytes;
+        // SAFETY: See `place_bytes!`
+        #[allow(unused_unsafe)]
+        let buf = unsafe { $crate::__private::bytes_uninit_checked(bytes) };
+        match buf {
+            ::core::result::Result::Ok(buf) => {
+                ::core::result::Result::Ok($crate::place!(buf, $($t)+))
+            }
+            ::core::result::Result::Err(e) => ::core::result::Result::Err(e),
+        }
+    }};
+}
+
+/// Treat the start of `bytes` as a [`MaybeUninit`], panicking if it doesn't
+/// fit
+///
+/// # Safety
+///
+/// See [`place_bytes!`]
+#[track_caller]
+pub unsafe fn bytes_uninit<T>(bytes: &mut [u8]) -> &mut MaybeUninit<T> {
+    // SAFETY: Caller upholds the requirements
+    match unsafe { bytes_uninit_checked(bytes) } {
+        Ok(buf) => buf,
+        Err(e) => panic!("{e}"),
+    }
+}
+
+/// Treat the start of `bytes` as a [`MaybeUninit`], if it fits
+///
+/// # Safety
+///
+/// See [`place_bytes!`]
+pub unsafe fn bytes_uninit_checked<T>(bytes: &mut [u8]) -> Result<&mut MaybeUninit<T>, PlaceError> {
+    check_fit::<T>(bytes)?;
+    // SAFETY: bytes is large enough and aligned for T, and the caller ensures
+    // it's never read as bytes while uninitialized
+    Ok(unsafe { &mut *bytes.as_mut_ptr().cast() })
+}
+
+/// Split `bytes` into a [`MaybeUninit`] at the first aligned offset, and the
+/// bytes after it, panicking if it doesn't fit
+#[track_caller]
+pub fn bump_uninit<T>(
+    bytes: &mut [MaybeUninit<u8>],
+) -> (&mut MaybeUninit<T>, &mut [MaybeUninit<u8>]) {
+    let len = bytes.len();
+    match try_bump_uninit(bytes) {
+        Ok(split) => split,
+        Err(_) => panic!(
+            "buffer of {len} bytes is too small for a type of {} bytes, aligned to {}",
+            size_of::<T>(),
+            align_of::<T>(),
+        ),
+    }
+}
+
+/// Split `bytes` into a [`MaybeUninit`] at the first aligned offset, and the
+/// bytes after it, if it fits
+///
+/// # Errors
+///
+/// If `T` doesn't fit in the bytes after that offset
+pub fn try_bump_uninit<T>(
+    bytes: &mut [MaybeUninit<u8>],
+) -> Result<(&mut MaybeUninit<T>, &mut [MaybeUninit<u8>]), PlaceError> {
+    let (size, _) = size_and_align::<T>();
+    let offset = bytes.as_ptr().align_offset(align_of::<T>());
+    let got = bytes.len().saturating_sub(offset);
+    if got < size {
+        return Err(PlaceError::TooSmall { needed: size, got });
+    }
+    let (buf, rest) = bytes[offset..].split_at_mut(size);
+    // SAFETY: buf is large enough and aligned for T, and uninitialized bytes
+    // may hold anything
+    let buf = unsafe { &mut *buf.as_mut_ptr().cast::<MaybeUninit<T>>() };
+    Ok((buf, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{explode, panic_line},
+        PlaceError,
+    };
+    use core::mem::{align_of, size_of, MaybeUninit};
+
+    #[derive(Debug, PartialEq)]
+    #[repr(C)]
+    struct Header {
+        magic: u32,
+        len: u16,
+        flags: u16,
+    }
+
+    #[repr(C, align(8))]
+    struct Region([u8; 16]);
+
+    #[derive(Debug, PartialEq)]
+    struct Tag(u8);
+
+    #[derive(Debug)]
+    struct Wide(u16);
+
+    #[test]
+    fn bytes() {
+        let mut region = Region([0; 16]);
+
+        let x: &mut Header = place_bytes!(
+            // SAFETY: Header has no padding
+            unsafe { &mut region.0[8..] },
+            Header {
+                magic: 0xCAFE,
+                len: 8,
+                flags: 1,
+            }
+        );
+        assert_eq!(
+            x,
+            &Header {
+                magic: 0xCAFE,
+                len: 8,
+                flags: 1,
+            }
+        );
+        assert_eq!(region.0[8..12], 0xCAFEu32.to_ne_bytes());
+    }
+
+    #[test]
+    #[should_panic = "not aligned to 4 bytes"]
+    fn bytes_misaligned() {
+        let mut region = Region([0; 16]);
+
+        place_bytes!(
+            // SAFETY: Header has no padding
+            unsafe { &mut region.0[1..] },
+            Header {
+                magic: 0xCAFE,
+                len: 8,
+                flags: 1,
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic = "too small"]
+    fn bytes_too_small() {
+        let mut region = Region([0; 16]);
+
+        place_bytes!(
+            // SAFETY: Header has no padding
+            unsafe { &mut region.0[12..] },
+            Header {
+                magic: 0xCAFE,
+                len: 8,
+                flags: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn bump() {
+        let mut storage = [MaybeUninit::uninit(); 32];
+        let start = storage.as_ptr() as usize;
+
+        let (tag, rest) = place_bump!(&mut storage, Tag(1));
+        let (header, rest) = place_bump!(
+            rest,
+            Header {
+                magic: 0xCAFE,
+                len: 8,
+                flags: 1,
+            }
+        );
+        assert_eq!(tag, &Tag(1));
+        assert_eq!(header.magic, 0xCAFE);
+
+        // Header follows the tag after padding to its alignment
+        let offset = header as *mut Header as usize - start;
+        assert!(offset > 0 && offset.is_multiple_of(align_of::<Header>()));
+        assert_eq!(rest.len(), 32 - offset - size_of::<Header>());
+    }
+
+    #[test]
+    #[should_panic = "too small"]
+    fn bump_too_small() {
+        let mut storage = [MaybeUninit::uninit(); 8];
+
+        let (_, rest) = place_bump!(&mut storage, Tag(1));
+        let _ = place_bump!(
+            rest,
+            Header {
+                magic: 0xCAFE,
+                len: 8,
+                flags: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn try_bump() {
+        let mut storage = [MaybeUninit::uninit(); 16];
+
+        let (tag, rest) = try_place_bump!(&mut storage, Tag(1)).unwrap();
+        assert_eq!(tag, &Tag(1));
+        let (header, rest) = try_place_bump!(
+            rest,
+            Header {
+                magic: 0xCAFE,
+                len: 8,
+                flags: 1,
+            }
+        )
+        .unwrap();
+        assert_eq!(header.magic, 0xCAFE);
+
+        // There are fewer bytes left than a header needs, once aligned
+        let got = rest.len() - rest.as_ptr().align_offset(align_of::<Header>());
+        let err = try_place_bump!(
+            rest,
+            Header {
+                magic: explode(),
+                len: 8,
+                flags: 1,
+            }
+        );
+        assert_eq!(
+            err.err(),
+            Some(PlaceError::TooSmall {
+                needed: size_of::<Header>(),
+                got
+            })
+        );
+    }
+
+    #[test]
+    fn bytes_location() {
+        let mut region = Region([0; 16]);
+
+        let line = panic_line(|| {
+            // SAFETY: Tag has no padding
+            place_bytes!(unsafe { &mut region.0[16..] }, Tag(1));
+        });
+        assert_eq!(line, line!() - 2);
+
+        let mut storage: [MaybeUninit<u8>; 0] = [];
+        let line = panic_line(|| {
+            let _ = place_bump!(&mut storage, Tag(1));
+        });
+        assert_eq!(line, line!() - 2);
+    }
+
+    #[test]
+    fn fit() {
+        let region = Region([0; 16]);
+
+        assert!(fits!(&region.0, Header));
+        assert!(fits!(&region.0[8..], Header));
+        assert!(!fits!(&region.0[9..], Header));
+        assert!(!fits!(&region.0[12..], Header));
+        assert!(fits!(&region.0[16..], ()));
+    }
+
+    #[test]
+    fn bytes_checked() {
+        let mut region = Region([0; 16]);
+
+        let x = place_bytes_checked!(
+            // SAFETY: Header has no padding
+            unsafe { &mut region.0 },
+            Header {
+                magic: 0xCAFE,
+                len: 8,
+                flags: 1,
+            }
+        );
+        assert_eq!(x.unwrap().magic, 0xCAFE);
+
+        let addr = region.0[1..].as_ptr() as usize;
+        // SAFETY: Wide has no padding
+        let err = place_bytes_checked!(unsafe { &mut region.0[1..] }, Wide(1));
+        assert_eq!(
+            err.unwrap_err(),
+            PlaceError::Misaligned { required: 2, addr }
+        );
+
+        // SAFETY: Tag has no padding
+        let err = place_bytes_checked!(unsafe { &mut region.0[16..] }, Tag(1));
+        assert_eq!(err.unwrap_err(), PlaceError::TooSmall { needed: 1, got: 0 });
+    }
+}
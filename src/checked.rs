@@ -0,0 +1,257 @@
+//! Uninitialized buffers that track whether they've been initialized
+
+use core::{marker::PhantomData, mem::MaybeUninit};
+
+/// A [`MaybeUninit`] which, with `debug_assertions`, tracks whether it has
+/// been initialized, to catch mistakes when managing it manually
+///
+/// Values are placed with [`place_checked!`](crate::place_checked), and
+/// checked with [`assert_init!`](crate::assert_init). Placing into a buffer
+/// that's already initialized, such as forgetting
+/// [`Checked::assume_init_drop`] before reusing it, panics instead of leaking
+/// or overwriting the old value.
+///
+/// Without `debug_assertions` nothing is tracked, and this has the same
+/// layout as `MaybeUninit<T>`.
+pub struct Checked<T> {
+    buf: MaybeUninit<T>,
+    #[cfg(debug_assertions)]
+    init: bool,
+}
+
+impl<T> Checked<T> {
+    /// Create a new uninitialized buffer
+    pub const fn uninit() -> Self {
+        Self {
+            buf: MaybeUninit::uninit(),
+            #[cfg(debug_assertions)]
+            init: false,
+        }
+    }
+
+    /// Start initializing the buffer, returning it and the flag to set once
+    /// it has been initialized
+    ///
+    /// # Panics
+    ///
+    /// With `debug_assertions`, if the buffer is already initialized
+    #[doc(hidden)]
+    #[track_caller]
+    pub fn __start(&mut self) -> (&mut MaybeUninit<T>, InitFlag<'_>) {
+        #[cfg(debug_assertions)]
+        assert!(!self.init, "placing into an already initialized buffer");
+        let flag = InitFlag {
+            #[cfg(debug_assertions)]
+            init: &mut self.init,
+            _marker: PhantomData,
+        };
+        (&mut self.buf, flag)
+    }
+
+    /// Assert the buffer has been initialized
+    ///
+    /// # Panics
+    ///
+    /// With `debug_assertions`, if the buffer isn't initialized
+    #[doc(hidden)]
+    #[track_caller]
+    pub fn __assert_init(&self) {
+        #[cfg(debug_assertions)]
+        assert!(self.init, "buffer is not initialized");
+    }
+
+    /// Drop the value in the buffer, leaving it uninitialized
+    ///
+    /// # Safety
+    ///
+    /// The buffer must be initialized, which is asserted with
+    /// `debug_assertions`
+    #[track_caller]
+    pub unsafe fn assume_init_drop(&mut self) {
+        self.__assert_init();
+        #[cfg(debug_assertions)]
+        {
+            self.init = false;
+        }
+        // SAFETY: Caller ensures buf is initialized
+        unsafe { self.buf.assume_init_drop() }
+    }
+}
+
+/// Whether a [`Checked`] buffer is initialized, borrowed while it's being
+/// initialized
+#[doc(hidden)]
+pub struct InitFlag<'a> {
+    #[cfg(debug_assertions)]
+    init: &'a mut bool,
+    _marker: PhantomData<&'a mut bool>,
+}
+
+impl InitFlag<'_> {
+    /// Record that the buffer has been initialized
+    pub fn set(self) {
+        #[cfg(debug_assertions)]
+        {
+            *self.init = true;
+        }
+    }
+}
+
+impl<T> Default for Checked<T> {
+    fn default() -> Self {
+        Self::uninit()
+    }
+}
+
+/// Initialize a struct in-place in the [`Checked`] buffer `buf`, and return a
+/// mutable reference
+///
+/// This works exactly like [`place!`], and with `debug_assertions` records
+/// that `buf` has been initialized.
+///
+/// # Panics
+///
+/// With `debug_assertions`, if `buf` is already initialized
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{assert_init, place_checked, Checked};
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// let mut buf = Checked::uninit();
+///
+/// let x: &mut MyCoolStruct = place_checked!(
+///     buf,
+///     MyCoolStruct {
+///         b: true,
+///         s: String::from("works"),
+///     }
+/// );
+/// assert_init!(buf);
+///
+/// // SAFETY: buf has been initialized above
+/// unsafe { buf.assume_init_drop() };
+/// ```
+///
+/// [`place!`]: crate::place
+#[macro_export]
+macro_rules! place_checked {
+    ($buf:expr, $($t:tt)+) => {{
+        use $crate::__private::AsPlace as _;
+        let buf: &mut $crate::Checked<_> = $buf.__as_place();
+        let (buf, flag) = buf.__start();
+        let init = $crate::place!(buf, $($t)+);
+        flag.set();
+        init
+    }};
+}
+
+/// Assert that the [`Checked`] buffer `buf` has been initialized
+///
+/// # Panics
+///
+/// With `debug_assertions`, if `buf` isn't initialized
+///
+/// # Examples
+///
+/// See [`place_checked!`](crate::place_checked)
+#[macro_export]
+macro_rules! assert_init {
+    ($buf:expr $(,)?) => {{
+        use $crate::__private::AsPlace as _;
+        let buf: &mut $crate::Checked<_> = $buf.__as_place();
+        buf.__assert_init()
+    }};
+}
+
+#[cfg(all(test, debug_assertions))]
+mod tests {
+    use super::Checked;
+    use crate::tests::{explode, DropCount};
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    struct Slot<'a> {
+        count: DropCount<'a>,
+        n: u8,
+    }
+
+    #[test]
+    fn checked() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = Checked::uninit();
+
+        let x: &mut Slot = place_checked!(
+            buf,
+            Slot {
+                count: DropCount(&drops),
+                n: 1,
+            }
+        );
+        assert_eq!(x.n, 1);
+        assert_init!(buf);
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+
+        // Can be reused once dropped
+        place_checked!(
+            buf,
+            Slot {
+                count: DropCount(&drops),
+                n: 2,
+            }
+        );
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    #[should_panic = "already initialized"]
+    fn checked_twice() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = Checked::uninit();
+
+        place_checked!(
+            buf,
+            Slot {
+                count: DropCount(&drops),
+                n: 1,
+            }
+        );
+        place_checked!(
+            buf,
+            Slot {
+                count: DropCount(&drops),
+                n: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn checked_panic() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = Checked::uninit();
+
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            place_checked!(
+                buf,
+                Slot {
+                    count: DropCount(&drops),
+                    n: explode(),
+                }
+            );
+        }));
+        assert!(res.is_err());
+        // Still uninitialized
+        let res = catch_unwind(AssertUnwindSafe(|| assert_init!(buf)));
+        assert!(res.is_err());
+    }
+}
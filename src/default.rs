@@ -0,0 +1,152 @@
+//! Placing the default value of every field
+
+/// Initialize a struct in-place at `buf` with the default value of each of
+/// its fields, without listing them, and return a mutable reference
+///
+/// This works exactly like [`place_default!`], except the fields are those of
+/// the struct, which must derive either [`PlaceDefault`] or [`FieldOrder`].
+/// Like for [`place_decl_order!`], it must be named by a plain path, such as
+/// `Config` or `config::Config`, from within the crate that defines it.
+///
+/// Each field is written directly with [`Default::default`], so the whole
+/// struct is never built on the stack, and needn't implement [`Default`]
+/// itself.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{emplace_default, PlaceDefault};
+/// # use std::mem::MaybeUninit;
+/// #[derive(PlaceDefault)]
+/// struct Config {
+///     name: String,
+///     verbose: bool,
+///     paths: Vec<String>,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut Config = emplace_default!(buf, Config);
+/// assert!(x.name.is_empty() && !x.verbose);
+///
+/// # // SAFETY: buf has been initialized above
+/// # unsafe { buf.assume_init_drop() };
+/// ```
+///
+/// [`place_default!`]: crate::place_default
+/// [`PlaceDefault`]: crate::PlaceDefault
+/// [`FieldOrder`]: crate::FieldOrder
+/// [`place_decl_order!`]: crate::place_decl_order
+#[macro_export]
+macro_rules! emplace_default {
+    ($buf:expr, $($path:ident)::+ $(,)?) => {
+        // Defined by the derive, and calls back with the fields
+        $($path)::+!(@fields [$crate::__emplace_default] $buf, [$($path)::+])
+    };
+}
+
+/// Gives each field of the struct its default value
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __emplace_default {
+    ([$($f:ident)*] $buf:expr, [$($path:tt)+]) => {
+        $crate::__place_default!($buf, [$($path)+], $($f),*)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{place_decl_order, PlaceDefault};
+    use core::mem::MaybeUninit;
+    use std::{boxed::Box, string::String, vec::Vec};
+
+    #[derive(Debug, PartialEq, PlaceDefault)]
+    struct Config {
+        name: String,
+        verbose: bool,
+        level: Option<u8>,
+    }
+
+    #[test]
+    fn emplace_default() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Config = emplace_default!(buf, Config);
+        assert_eq!(
+            *x,
+            Config {
+                name: String::new(),
+                verbose: false,
+                level: None,
+            }
+        );
+
+        // Through a reference, and a qualified path
+        let x: &mut Config = emplace_default!(&mut buf, self::Config);
+        assert!(x.name.is_empty());
+
+        // The fields are recorded like `FieldOrder` does
+        let x: &mut Config = place_decl_order!(
+            buf,
+            Config {
+                level: Some(1),
+                verbose: true,
+                name: String::from("works"),
+            }
+        );
+        assert_eq!(x.level, Some(1));
+    }
+
+    /// Stands in for a large field, but isn't itself too large to build on
+    /// the stack
+    #[derive(Clone, Copy)]
+    struct Page([u8; 1 << 14]);
+
+    impl Default for Page {
+        fn default() -> Self {
+            Page([1; 1 << 14])
+        }
+    }
+
+    #[derive(PlaceDefault)]
+    struct Pages {
+        a: Page,
+        b: Page,
+        c: Page,
+        d: Page,
+        e: Page,
+        f: Page,
+        g: Page,
+        h: Page,
+        i: Page,
+        j: Page,
+        k: Page,
+        l: Page,
+        m: Page,
+        n: Page,
+        o: Page,
+        p: Page,
+        list: Vec<u8>,
+    }
+
+    #[test]
+    #[cfg_attr(debug_assertions, ignore = "each field's temporary is kept")]
+    fn emplace_default_large() {
+        // Pages is twice the size of the stack, so building it there would
+        // overflow it, while each page fits. Without optimizations, each page
+        // still takes its own space, so this only runs with `--release`.
+        let pages = std::thread::Builder::new()
+            .stack_size(size_of::<Pages>() / 2)
+            .spawn(|| {
+                let mut buf: Box<MaybeUninit<Pages>> = Box::new_uninit();
+                emplace_default!(&mut *buf, Pages);
+                // SAFETY: buf has been initialized above
+                unsafe { buf.assume_init() }
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        assert!(pages.a.0.iter().chain(&pages.p.0).all(|b| *b == 1));
+        assert!(pages.list.is_empty());
+    }
+}
@@ -0,0 +1,529 @@
+//! Placing structs with a trailing slice
+
+use crate::{array::SliceGuard, PlaceError};
+
+/// Initialize a struct ending in a slice in-place at the raw pointer `ptr`,
+/// and return a mutable reference
+///
+/// `ptr` is a wide `*mut T`, whose metadata is the length of the slice. The
+/// last field given must be the slice, and its value anything that can be
+/// iterated to produce exactly that many elements, such as
+/// `bytes.iter().copied()`. The other fields are written like [`place!`], in
+/// order, followed by the slice elements.
+///
+/// Like `place!`, fields may be given as shorthand, and have attributes such
+/// as `#[cfg]`. Nested `place!` fields, `@first` and `@last`, and `..base`
+/// are not supported.
+///
+/// Like [`place_pin!`], `ptr` is given as an `unsafe` block, and field
+/// initializers aren't evaluated in it.
+///
+/// If an initializer panics, the fields and elements already written are
+/// dropped.
+///
+/// # Panics
+///
+/// If the slice initializer produces too few or too many elements
+///
+/// # Safety
+///
+/// See [`place_ptr!`], for the size of `T` with the length of `ptr`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_dst;
+/// # use std::{mem::MaybeUninit, ptr};
+///
+/// #[repr(C)]
+/// struct Packet {
+///     len: u32,
+///     body: [u8],
+/// }
+///
+/// let bytes = [1, 2, 3, 4];
+///
+/// let mut storage = MaybeUninit::<[u32; 2]>::uninit();
+/// let ptr = ptr::slice_from_raw_parts_mut(storage.as_mut_ptr().cast::<u8>(), 4);
+/// let ptr = ptr as *mut Packet;
+///
+/// let x: &mut Packet = place_dst!(
+///     // SAFETY: storage is large enough and aligned for a Packet with 4 bytes
+///     unsafe { ptr },
+///     Packet {
+///         len: 4,
+///         body: bytes.iter().copied(),
+///     }
+/// );
+/// assert_eq!(x.body, [1, 2, 3, 4]);
+/// ```
+///
+/// [`place!`]: crate::place
+/// [`place_pin!`]: crate::place_pin
+/// [`place_ptr!`]: crate::place_ptr
+#[macro_export]
+macro_rules! place_dst {
+    ($unsafe:tt $ptr:tt, $($t:tt)+) => {{
+        $crate::__place_unsafe!($unsafe);
+        #[allow(unused_unsafe)]
+        let ptr = $unsafe $ptr;
+        $crate::__place_path!(@dst __place_dst ptr, [], $($t)+)
+    }};
+}
+
+/// Initialize a struct ending in a slice in-place at the raw pointer `ptr`,
+/// and return a mutable reference, or an error if the slice isn't filled
+///
+/// This works exactly like [`place_dst!`], except it returns a
+/// [`PlaceError::TooFewElements`] or [`PlaceError::TooManyElements`] instead
+/// of panicking. The fields and elements already written are dropped first,
+/// along with the extra element if there were too many.
+///
+/// # Errors
+///
+/// If the slice initializer produces too few or too many elements
+///
+/// # Safety
+///
+/// See [`place_dst!`]
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{try_place_dst, PlaceError};
+/// # use std::{mem::MaybeUninit, ptr};
+///
+/// #[repr(C)]
+/// struct Packet {
+///     len: u32,
+///     body: [u8],
+/// }
+///
+/// let mut storage = MaybeUninit::<[u32; 2]>::uninit();
+/// let ptr = ptr::slice_from_raw_parts_mut(storage.as_mut_ptr().cast::<u8>(), 4);
+/// let ptr = ptr as *mut Packet;
+///
+/// let x = try_place_dst!(
+///     // SAFETY: storage is large enough and aligned for a Packet with 4 bytes
+///     unsafe { ptr },
+///     Packet {
+///         len: 4,
+///         body: [1, 2, 3],
+///     }
+/// );
+/// assert!(matches!(x, Err(PlaceError::TooFewElements { needed: 4, got: 3 })));
+/// ```
+///
+/// [`place_dst!`]: crate::place_dst
+/// [`PlaceError::TooFewElements`]: crate::PlaceError::TooFewElements
+/// [`PlaceError::TooManyElements`]: crate::PlaceError::TooManyElements
+#[macro_export]
+macro_rules! try_place_dst {
+    ($unsafe:tt $ptr:tt, $($t:tt)+) => {{
+        $crate::__place_unsafe!($unsafe);
+        #[allow(unused_unsafe)]
+        let ptr = $unsafe $ptr;
+        $crate::__place_path!(@dst __try_place_dst ptr, [], $($t)+)
+    }};
+}
+
+/// Collects the fields like `__place_dst`, returning an error for the slice
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __try_place_dst {
+    ($ptr:expr, $($t:tt)+) => {
+        $crate::__place_dst!(@try $ptr, $($t)+)
+    };
+}
+
+/// Collects the sized fields, until the trailing slice
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_dst {
+    // Writes the trailing slice, and finishes
+    (
+        @tail [$(#[$attr:meta])*]
+        $ptr:ident, $guard:ident, $tail:ident, $tail_val:expr
+    ) => {{
+        $(#[$attr])*
+        {
+            let iter = $tail_val;
+            // SAFETY: Caller ensures ptr is valid for writes, and the length
+            // of the slice is that of ptr
+            #[allow(unused_unsafe)]
+            unsafe {
+                $crate::__private::place_tail(::core::ptr::addr_of_mut!((*$ptr).$tail), iter)
+            };
+        }
+        ::core::mem::forget($guard);
+        // SAFETY: All fields have been initialized above
+        #[allow(unused_unsafe)]
+        let init = unsafe { &mut *$ptr };
+        init
+    }};
+
+    (
+        @tail try [$(#[$attr:meta])*]
+        $ptr:ident, $guard:ident, $tail:ident, $tail_val:expr
+    ) => {{
+        // Unused unless the slice is configured out
+        #[allow(unused_variables)]
+        let res: ::core::result::Result<(), $crate::PlaceError> = ::core::result::Result::Ok(());
+        $(#[$attr])*
+        let res = {
+            let iter = $tail_val;
+            // SAFETY: See above
+            #[allow(unused_unsafe)]
+            unsafe {
+                $crate::__private::try_place_tail(::core::ptr::addr_of_mut!((*$ptr).$tail), iter)
+            }
+        };
+        match res {
+            ::core::result::Result::Ok(()) => {
+                ::core::mem::forget($guard);
+                // SAFETY: All fields have been initialized above
+                #[allow(unused_unsafe)]
+                let init = unsafe { &mut *$ptr };
+                ::core::result::Result::Ok(init)
+            }
+            // The guard drops the fields
+            ::core::result::Result::Err(e) => ::core::result::Result::Err(e),
+        }
+    }};
+
+    (
+        $(@$mode:ident)? $ptr:expr,
+        [$($path:tt)+],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        #[doc $($doc:tt)*] $($rest:tt)*
+    ) => {
+        $crate::__place_dst!($(@$mode)? $ptr, [$($path)+], [$($done)*], [$($attrs)*], $($rest)*)
+    };
+
+    (
+        $(@$mode:ident)? $ptr:expr,
+        [$($path:tt)+],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        #[$attr:meta] $($rest:tt)*
+    ) => {
+        $crate::__place_dst!($(@$mode)? $ptr, [$($path)+], [$($done)*], [$($attrs)* #[$attr]], $($rest)*)
+    };
+
+    (
+        $(@$mode:ident)? $ptr:expr,
+        [$($path:tt)+],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        $next:ident: $next_val:expr, $($rest:tt)+
+    ) => {
+        $crate::__place_dst!(
+            $(@$mode)? $ptr,
+            [$($path)+],
+            [$($done)* [$($attrs)*] ($next) ($next_val)],
+            [],
+            $($rest)+
+        )
+    };
+
+    (
+        $(@$mode:ident)? $ptr:expr,
+        [$($path:tt)+],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        $next:ident, $($rest:tt)+
+    ) => {
+        $crate::__place_dst!(
+            $(@$mode)? $ptr,
+            [$($path)+],
+            [$($done)* [$($attrs)*] ($next) ($next)],
+            [],
+            $($rest)+
+        )
+    };
+
+    (
+        $(@$mode:ident)? $ptr:expr,
+        [$($path:tt)+],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        $tail:ident $(,)?
+    ) => {
+        $crate::__place_dst!($(@$mode)? $ptr, [$($path)+], [$($done)*], [$($attrs)*], $tail: $tail)
+    };
+
+    (
+        $(@$mode:ident)? $ptr:expr,
+        [$($path:tt)+],
+        [$([$(#[$attr:meta])*] ($f:ident) ($f_val:expr))*],
+        [$(#[$tail_attr:meta])*],
+        $tail:ident: $tail_val:expr $(,)?
+    ) => {{
+        let ptr = $ptr;
+        // The type can't be constructed, so a pattern checks that all fields
+        // were given instead.
+        $crate::__private::check_dst(ptr, |v| {
+            let $($path)+ { $($(#[$attr])* $f: _,)* $(#[$tail_attr])* $tail: _ } = *v;
+        });
+        // See `__place_fields`
+        #[allow(unused_unsafe, unused_mut)]
+        let mut guard = $crate::__private::DropGuard::new(ptr, &[
+            $(
+                $(#[$attr])*
+                |ptr| {
+                    // SAFETY: The guard only calls this for fields that have
+                    // been written
+                    unsafe { $crate::__private::drop_field(::core::ptr::addr_of_mut!((*ptr).$f)) }
+                }
+            ),*
+        ]);
+        $(
+            $(#[$attr])*
+            {
+                // Caller ensures ptr is valid for writes
+                $crate::__place_write!(__place_fields ptr, $f, (value $f_val));
+                guard.written();
+            }
+        )*
+        $crate::__place_dst!(
+            @tail $($mode)? [$(#[$tail_attr])*]
+            ptr, guard, $tail, $tail_val
+        )
+    }};
+}
+
+/// Ties the fields matched by `check` to the type of `ptr`
+///
+/// `check` is never called.
+pub fn check_dst<T: ?Sized>(ptr: *mut T, check: fn(&T)) {
+    let _ = (ptr, check);
+}
+
+/// Write each item of `iter` to `tail`
+///
+/// # Panics
+///
+/// If `iter` doesn't produce exactly `tail.len()` items.
+///
+/// # Safety
+///
+/// `tail` must be valid for writes
+#[track_caller]
+pub unsafe fn place_tail<T>(tail: *mut [T], iter: impl IntoIterator<Item = T>) {
+    // SAFETY: Caller ensures tail is valid for writes
+    if let Err(e) = unsafe { try_place_tail(tail, iter) } {
+        panic!("{e}");
+    }
+}
+
+/// Write each item of `iter` to `tail`, dropping them unless it produces
+/// exactly `tail.len()` items
+///
+/// # Errors
+///
+/// If `iter` doesn't produce exactly `tail.len()` items.
+///
+/// # Safety
+///
+/// `tail` must be valid for writes
+pub unsafe fn try_place_tail<T>(
+    tail: *mut [T],
+    iter: impl IntoIterator<Item = T>,
+) -> Result<(), PlaceError> {
+    let len = tail.len();
+    let ptr = tail.cast::<T>();
+    let mut iter = iter.into_iter();
+    let mut guard = SliceGuard { ptr, init: 0 };
+    for i in 0..len {
+        let Some(val) = iter.next() else {
+            return Err(PlaceError::TooFewElements {
+                needed: len,
+                got: i,
+            });
+        };
+        // SAFETY: `i` is in bounds of tail
+        unsafe { ptr.add(i).write(val) };
+        guard.init += 1;
+    }
+    if iter.next().is_some() {
+        return Err(PlaceError::TooManyElements { len });
+    }
+    core::mem::forget(guard);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{explode, DropCount},
+        PlaceError,
+    };
+    use core::{
+        mem::MaybeUninit,
+        ptr,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    #[derive(Debug, PartialEq)]
+    struct Header {
+        kind: u16,
+        flags: u16,
+    }
+
+    #[repr(C)]
+    struct Packet {
+        header: Header,
+        body: [u8],
+    }
+
+    #[test]
+    fn dst() {
+        let mut storage = MaybeUninit::<[u32; 2]>::uninit();
+        let ptr =
+            ptr::slice_from_raw_parts_mut(storage.as_mut_ptr().cast::<u8>(), 4) as *mut Packet;
+
+        let x: &mut Packet = place_dst!(
+            // SAFETY: storage is large enough and aligned for a Packet with 4
+            // bytes
+            unsafe { ptr },
+            Packet {
+                header: Header { kind: 1, flags: 2 },
+                body: [1, 2, 3, 4],
+            }
+        );
+        assert_eq!(x.header, Header { kind: 1, flags: 2 });
+        assert_eq!(x.body, [1, 2, 3, 4]);
+    }
+
+    #[repr(C)]
+    struct Tagged {
+        id: u64,
+        len: u8,
+        #[cfg(any())]
+        missing: u8,
+        body: [u8],
+    }
+
+    #[test]
+    fn dst_fields() {
+        let mut storage = MaybeUninit::<[u64; 3]>::uninit();
+        let ptr =
+            ptr::slice_from_raw_parts_mut(storage.as_mut_ptr().cast::<u8>(), 3) as *mut Tagged;
+        let len = 3;
+        let body = [1, 2, 3];
+
+        let x: &mut Tagged = place_dst!(
+            // SAFETY: storage is large enough and aligned for Tagged with 3
+            // bytes
+            unsafe { ptr },
+            Tagged {
+                /// Ignored, like with `place!`
+                id: 1 << 40,
+                len,
+                #[cfg(any())]
+                missing: 0,
+                body,
+            }
+        );
+        assert_eq!(x.id, 1 << 40);
+        assert_eq!(x.len, 3);
+        assert_eq!(x.body, [1, 2, 3]);
+    }
+
+    #[repr(C)]
+    struct Counted<'a> {
+        first: DropCount<'a>,
+        rest: [DropCount<'a>],
+    }
+
+    #[test]
+    fn dst_panic() {
+        let drops = AtomicUsize::new(0);
+        let mut storage = MaybeUninit::<[DropCount; 4]>::uninit();
+        let ptr = ptr::slice_from_raw_parts_mut(storage.as_mut_ptr().cast::<DropCount>(), 3)
+            as *mut Counted;
+
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            let _ = place_dst!(
+                // SAFETY: storage is large enough and aligned for Counted with
+                // 3 elements
+                unsafe { ptr },
+                Counted {
+                    first: DropCount(&drops),
+                    rest: (0..3).map(|i| if i == 2 { explode() } else { DropCount(&drops) }),
+                }
+            );
+        }));
+        assert!(res.is_err());
+        assert_eq!(drops.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    #[should_panic = "too few elements"]
+    fn dst_too_few() {
+        let mut storage = MaybeUninit::<[u32; 2]>::uninit();
+        let ptr =
+            ptr::slice_from_raw_parts_mut(storage.as_mut_ptr().cast::<u8>(), 4) as *mut Packet;
+
+        let _ = place_dst!(
+            // SAFETY: storage is large enough and aligned for a Packet with 4
+            // bytes
+            unsafe { ptr },
+            Packet {
+                header: Header { kind: 1, flags: 2 },
+                body: [1, 2, 3],
+            }
+        );
+    }
+
+    #[test]
+    fn try_dst() {
+        let drops = AtomicUsize::new(0);
+        let mut storage = MaybeUninit::<[DropCount; 4]>::uninit();
+        let ptr = ptr::slice_from_raw_parts_mut(storage.as_mut_ptr().cast::<DropCount>(), 3)
+            as *mut Counted;
+
+        let x = try_place_dst!(
+            // SAFETY: storage is large enough and aligned for Counted with 3
+            // elements
+            unsafe { ptr },
+            Counted {
+                first: DropCount(&drops),
+                rest: (0..3).map(|_| DropCount(&drops)),
+            }
+        );
+        assert_eq!(x.unwrap().rest.len(), 3);
+        // SAFETY: ptr has been initialized above
+        unsafe { ptr.drop_in_place() };
+        assert_eq!(drops.load(Ordering::Relaxed), 4);
+
+        // The field and elements written are dropped
+        let x = try_place_dst!(
+            // SAFETY: See above
+            unsafe { ptr },
+            Counted {
+                first: DropCount(&drops),
+                rest: (0..2).map(|_| DropCount(&drops)),
+            }
+        );
+        assert_eq!(
+            x.err(),
+            Some(PlaceError::TooFewElements { needed: 3, got: 2 })
+        );
+        assert_eq!(drops.load(Ordering::Relaxed), 7);
+
+        // Along with the extra element
+        let x = try_place_dst!(
+            // SAFETY: See above
+            unsafe { ptr },
+            Counted {
+                first: DropCount(&drops),
+                rest: (0..5).map(|_| DropCount(&drops)),
+            }
+        );
+        assert_eq!(x.err(), Some(PlaceError::TooManyElements { len: 3 }));
+        assert_eq!(drops.load(Ordering::Relaxed), 12);
+    }
+}
@@ -0,0 +1,88 @@
+//! Errors for buffers that can't be placed into
+
+use core::{alloc::Layout, fmt};
+
+/// Error returned when a value can't be placed into its buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceError {
+    /// The buffer is smaller than the type
+    TooSmall {
+        /// The size of the type
+        needed: usize,
+        /// The size of the buffer
+        got: usize,
+    },
+    /// The start of the buffer isn't aligned for the type
+    Misaligned {
+        /// The alignment of the type
+        required: usize,
+        /// The address of the buffer
+        addr: usize,
+    },
+    /// Allocating a buffer for the type failed
+    AllocFailed {
+        /// The layout of the type
+        layout: Layout,
+    },
+    /// The index of the slot is past the end of the slice
+    OutOfBounds {
+        /// The index of the slot
+        index: usize,
+        /// The length of the slice
+        len: usize,
+    },
+    /// There are fewer slots than values to place in them
+    TooFewSlots {
+        /// The number of values
+        needed: usize,
+        /// The number of slots
+        got: usize,
+    },
+    /// The iterator for a trailing slice ran out before filling it
+    TooFewElements {
+        /// The length of the slice
+        needed: usize,
+        /// The number of elements
+        got: usize,
+    },
+    /// The iterator for a trailing slice had elements left after filling it
+    TooManyElements {
+        /// The length of the slice
+        len: usize,
+    },
+}
+
+impl fmt::Display for PlaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooSmall { needed, got } => write!(
+                f,
+                "buffer of {got} bytes is too small for a type of {needed} bytes"
+            ),
+            Self::Misaligned { required, addr } => {
+                write!(f, "buffer at {addr:#x} is not aligned to {required} bytes")
+            }
+            Self::AllocFailed { layout } => write!(
+                f,
+                "failed to allocate {} bytes aligned to {}",
+                layout.size(),
+                layout.align()
+            ),
+            Self::OutOfBounds { index, len } => {
+                write!(f, "index {index} is out of bounds of {len} slots")
+            }
+            Self::TooFewSlots { needed, got } => {
+                write!(f, "{got} slots are too few for {needed} values")
+            }
+            Self::TooFewElements { needed, got } => write!(
+                f,
+                "too few elements for the trailing slice, {got} of {needed}"
+            ),
+            Self::TooManyElements { len } => {
+                write!(f, "too many elements for the trailing slice of {len}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for PlaceError {}
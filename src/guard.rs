@@ -0,0 +1,270 @@
+//! Owning guard for values placed on the stack
+
+use core::{
+    mem::{self, MaybeUninit},
+    ops::{Deref, DerefMut},
+};
+
+/// An initialized value in a borrowed [`MaybeUninit`], which is dropped in
+/// place along with the guard
+///
+/// Created by [`place_guard!`](crate::place_guard). Ignoring the guard drops
+/// the value straight away, which is warned about.
+///
+/// ```rust,compile_fail
+/// #![deny(unused_must_use)]
+/// # use place::place_guard;
+/// # use std::mem::MaybeUninit;
+/// # struct MyCoolStruct {
+/// #     b: bool,
+/// # }
+/// let mut buf = MaybeUninit::uninit();
+///
+/// place_guard!(buf, MyCoolStruct { b: true });
+/// ```
+#[must_use = "the initialized value must be used or dropped"]
+pub struct InitGuard<'a, T> {
+    buf: &'a mut MaybeUninit<T>,
+}
+
+impl<'a, T> InitGuard<'a, T> {
+    /// Take ownership of the value in `buf`
+    ///
+    /// # Safety
+    ///
+    /// `buf` must be initialized
+    pub unsafe fn new_unchecked(buf: &'a mut MaybeUninit<T>) -> Self {
+        Self { buf }
+    }
+
+    /// Move the value out of the buffer, leaving it uninitialized
+    pub fn into_inner(self) -> T {
+        let this = mem::ManuallyDrop::new(self);
+        // SAFETY: buf is initialized, and won't be used again
+        unsafe { this.buf.assume_init_read() }
+    }
+
+    /// Forget the guard, returning a reference to the value
+    ///
+    /// It is your responsibility to drop the value if needed.
+    pub fn leak(self) -> &'a mut T {
+        let this = mem::ManuallyDrop::new(self);
+        // SAFETY: The guard is never used again, so buf can be moved out.
+        let buf = unsafe { core::ptr::read(&this.buf) };
+        // SAFETY: buf is initialized
+        unsafe { buf.assume_init_mut() }
+    }
+}
+
+impl<T> Deref for InitGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: buf is initialized
+        unsafe { self.buf.assume_init_ref() }
+    }
+}
+
+impl<T> DerefMut for InitGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: buf is initialized
+        unsafe { self.buf.assume_init_mut() }
+    }
+}
+
+impl<T> Drop for InitGuard<'_, T> {
+    fn drop(&mut self) {
+        // SAFETY: buf is initialized
+        unsafe { self.buf.assume_init_drop() }
+    }
+}
+
+/// Initialize a struct in-place at `buf`, and return an [`InitGuard`] owning
+/// it
+///
+/// This works exactly like [`place!`](crate::place), except the value is
+/// dropped in place when the guard is, instead of being your responsibility.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{place_guard, InitGuard};
+/// # use std::mem::MaybeUninit;
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: InitGuard<MyCoolStruct> = place_guard!(
+///     buf,
+///     MyCoolStruct {
+///         b: true,
+///         s: String::from("works"),
+///     }
+/// );
+/// assert_eq!(x.s, "works");
+/// ```
+#[macro_export]
+macro_rules! place_guard {
+    ($buf:expr, $($t:tt)+) => {{
+        use $crate::__private::AsPlace as _;
+        let buf: &mut ::core::mem::MaybeUninit<_> = $buf.__as_place();
+        $crate::place!(*buf, $($t)+);
+        // SAFETY: buf has been initialized above
+        #[allow(unused_unsafe)]
+        let guard = unsafe { $crate::InitGuard::new_unchecked(buf) };
+        guard
+    }};
+}
+
+/// Declare an [`InitGuard`] owning a struct initialized in-place in a new
+/// buffer on the stack
+///
+/// This works exactly like [`place_guard!`](crate::place_guard), except the
+/// buffer is declared for you, hidden, just before the guard. As the buffer
+/// only exists for as long as the scope it's declared in, this declares the
+/// guard as a `let` statement, rather than being an expression which would
+/// need to outlive it.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{emplace, InitGuard};
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// emplace!(let mut x: InitGuard<MyCoolStruct> = MyCoolStruct {
+///     b: true,
+///     s: String::from("works"),
+/// });
+/// x.s.push_str(" well");
+/// assert_eq!(x.s, "works well");
+/// ```
+///
+/// The guard can't outlive the buffer
+///
+/// ```rust,compile_fail,E0597
+/// # use place::emplace;
+/// # struct MyCoolStruct {
+/// #     b: bool,
+/// # }
+/// let x = {
+///     emplace!(let x = MyCoolStruct { b: true });
+///     x
+/// };
+/// ```
+#[macro_export]
+macro_rules! emplace {
+    (let $name:ident $(: $typ:ty)? = $($t:tt)+) => {
+        let mut buf = ::core::mem::MaybeUninit::uninit();
+        let $name $(: $typ)? = $crate::place_guard!(buf, $($t)+);
+    };
+
+    (let mut $name:ident $(: $typ:ty)? = $($t:tt)+) => {
+        let mut buf = ::core::mem::MaybeUninit::uninit();
+        let mut $name $(: $typ)? = $crate::place_guard!(buf, $($t)+);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::DropCount;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Counted<'a> {
+        count: DropCount<'a>,
+        value: String,
+    }
+
+    #[test]
+    fn guard_drops() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = MaybeUninit::uninit();
+
+        let mut x: InitGuard<Counted> = place_guard!(
+            buf,
+            Counted {
+                count: DropCount(&drops),
+                value: String::from("works"),
+            }
+        );
+        x.value.push_str(" well");
+        assert_eq!(x.value, "works well");
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+
+        drop(x);
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn guard_into_inner() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = MaybeUninit::uninit();
+
+        let x = place_guard!(
+            buf,
+            Counted {
+                count: DropCount(&drops),
+                value: String::from("works"),
+            }
+        );
+        let x: Counted = x.into_inner();
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+        assert_eq!(x.value, "works");
+
+        drop(x);
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn guard_leak() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = MaybeUninit::uninit();
+
+        let x = place_guard!(
+            buf,
+            Counted {
+                count: DropCount(&drops),
+                value: String::from("works"),
+            }
+        );
+        let x: &mut Counted = x.leak();
+        assert_eq!(x.value, "works");
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+
+        // SAFETY: buf has been initialized above, and the guard leaked
+        unsafe { buf.assume_init_drop() };
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn emplace() {
+        let drops = AtomicUsize::new(0);
+
+        {
+            emplace!(let mut x = Counted {
+                count: DropCount(&drops),
+                value: String::from("works"),
+            });
+            x.value.push_str(" well");
+            assert_eq!(x.value, "works well");
+
+            // Each has its own buffer
+            emplace!(let y: InitGuard<Counted> = Counted {
+                count: DropCount(&drops),
+                value: String::from("too"),
+            });
+            assert_eq!(y.value, "too");
+            assert_eq!(x.value, "works well");
+            assert_eq!(drops.load(Ordering::Relaxed), 0);
+        }
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+    }
+}
@@ -1,9 +1,79 @@
 //! Placement new in Rust
-#![cfg_attr(not(test), no_std)]
+//!
+//! # Features
+//!
+//! With no features enabled this crate is `no_std` and never allocates, so
+//! it only needs `core`
+//!
+//! ```rust
+//! #![no_std]
+//! # // Doctests still need std to run
+//! # extern crate std;
+//! use core::mem::MaybeUninit;
+//! use place::place;
+//!
+//! struct MyCoolStruct {
+//!     b: bool,
+//!     u: u32,
+//! }
+//!
+//! pub fn init(buf: &mut MaybeUninit<MyCoolStruct>) -> &mut MyCoolStruct {
+//!     place!(buf, MyCoolStruct { b: true, u: 69420 })
+//! }
+//! # fn main() {
+//! #     assert!(init(&mut MaybeUninit::uninit()).b);
+//! # }
+//! ```
+//!
+//! - `alloc` adds the macros that allocate, such as `place_box!` and
+//!   `place_vec_push!`, using the `alloc` crate.
+//! - `std` implies `alloc`, and links `std`.
+//! - `derive` adds `#[derive(FieldOrder)]` and `#[derive(PlaceDefault)]`, for
+//!   `place_decl_order!` and `emplace_default!`.
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+mod array;
+#[cfg(feature = "alloc")]
+mod boxed;
+mod buffer;
+mod bytes;
+mod checked;
+#[cfg(feature = "derive")]
+mod default;
+mod dst;
+mod error;
+mod guard;
+#[cfg(feature = "derive")]
+mod order;
+mod placer;
+#[cfg(feature = "alloc")]
+mod rc;
+#[cfg(feature = "alloc")]
+mod sync;
+mod uninit;
+#[cfg(feature = "alloc")]
+mod vec;
+
+pub use buffer::{place_into, Place};
+pub use bytes::{check_fit, size_and_align};
+pub use checked::Checked;
+pub use error::PlaceError;
+pub use guard::InitGuard;
+#[cfg(feature = "derive")]
+pub use place_derive::{FieldOrder, PlaceDefault};
+pub use placer::{MissingFields, Placer};
+pub use uninit::Uninit;
 
 /// Initialize a struct in-place at `buf`, and return a mutable reference
 ///
-/// `buf` is a MaybeUninit of your type
+/// `buf` is a MaybeUninit of your type, an [`Uninit`], or any other [`Place`],
+/// or a mutable reference to one, such as from `slice.get_mut(i)`. References
+/// are used as is, so the returned reference can live as long as `buf` does.
+/// Field projections work too, including through a deref, such as
+/// `container.slot`, `boxed.slot`, or `borrow.slot` for a `RefCell` borrow.
 ///
 /// It is your responsibility to drop your type if needed when you're done with
 /// it.
@@ -11,7 +81,24 @@
 /// It is your responsibility to correctly produce the MaybeUninit
 ///
 /// This macro will ensure that all fields are initialized, and is thus
-/// safe to call.
+/// safe to call. It may still be used inside an `unsafe` block, without any
+/// `unused_unsafe` warnings.
+///
+/// If a field initializer panics, the fields already written are dropped,
+/// and `buf` is left uninitialized. They're dropped in the reverse of the
+/// order they were written, like the fields of a struct literal would be.
+///
+/// Field initializers are guaranteed to be evaluated, and written, in the
+/// order they are given, from left to right, regardless of the order the fields
+/// are declared in, which macros can't know. To write them in declaration
+/// order instead, use `place_decl_order!` with the `derive` feature. They
+/// may be any expression, including inline `const { .. }` blocks.
+///
+/// A field may be marked `@first` or `@last`, such as `@first lock: acquire()`,
+/// to be evaluated and written before or after all the others instead,
+/// wherever it's given. Fields marked the same keep the order they're given
+/// in. This only changes the order fields are written, not the layout of the
+/// type.
 ///
 /// # Examples
 ///
@@ -37,75 +124,5807 @@
 /// # // SAFETY: buf has been initialized above
 /// # unsafe { buf.assume_init_drop() };
 /// ```
+///
+/// Like struct literals, `field` may be used as shorthand for `field: field`,
+/// and fields may have attributes, such as `#[cfg(..)]` for types with fields
+/// that only exist in some configurations, or `#[allow(..)]`. These apply to
+/// both the field and its initializer.
+///
+/// Also like struct literals, all fields of the type must be given. A missing
+/// field is reported by rustc as if the literal had been written directly,
+/// naming the field and pointing at the type in the invocation, such as
+/// "missing field `s` in initializer of `MyCoolStruct`".
+///
+/// ```rust,compile_fail,E0063
+/// # use place::place;
+/// # use std::mem::MaybeUninit;
+/// # struct MyCoolStruct {
+/// #     b: bool,
+/// #     s: String,
+/// # }
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut MyCoolStruct = place!(buf, MyCoolStruct { b: true });
+/// ```
+///
+/// Giving a field more than once is an error in the same way, so no field is
+/// ever written twice.
+///
+/// ```rust,compile_fail,E0062
+/// # use place::place;
+/// # use std::mem::MaybeUninit;
+/// # struct MyCoolStruct {
+/// #     b: bool,
+/// #     s: String,
+/// # }
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut MyCoolStruct = place!(
+///     buf,
+///     MyCoolStruct {
+///         b: true,
+///         b: false,
+///         s: String::new(),
+///     }
+/// );
+/// ```
+///
+/// Fields the type doesn't have, such as a typo, are reported at the field
+/// name, such as "no field `bb` on type `MyCoolStruct`", along with any
+/// similarly named field.
+///
+/// ```rust,compile_fail,E0609
+/// # use place::place;
+/// # use std::mem::MaybeUninit;
+/// # struct MyCoolStruct {
+/// #     b: bool,
+/// #     s: String,
+/// # }
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut MyCoolStruct = place!(
+///     buf,
+///     MyCoolStruct {
+///         bb: true,
+///         s: String::new(),
+///     }
+/// );
+/// ```
+///
+/// Values of the wrong type are reported at the value, also like struct
+/// literals. The type of the field is known before its value, so values are
+/// coerced to it the same way too, such as closures to `fn` pointers, or
+/// `Box::new(|| ..)` to `Box<dyn Fn()>`.
+///
+/// ```rust,compile_fail,E0308
+/// # use place::place;
+/// # use std::mem::MaybeUninit;
+/// # struct MyCoolStruct {
+/// #     b: bool,
+/// #     s: String,
+/// # }
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut MyCoolStruct = place!(
+///     buf,
+///     MyCoolStruct {
+///         b: 1,
+///         s: String::new(),
+///     }
+/// );
+/// ```
+///
+/// `buf` is borrowed mutably until the value has been initialized, so field
+/// initializers can't use it, such as to read the fields already written.
+/// Doing so is a borrow error rather than a read of uninitialized memory.
+/// [`field!`] and [`field_ref!`] can be used instead.
+///
+/// ```rust,compile_fail,E0502
+/// # use place::place;
+/// # use std::mem::MaybeUninit;
+/// # struct MyCoolStruct {
+/// #     b: bool,
+/// #     s: String,
+/// # }
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut MyCoolStruct = place!(
+///     buf,
+///     MyCoolStruct {
+///         b: true,
+///         // SAFETY: Not really, but this doesn't compile anyway
+///         s: unsafe { buf.assume_init_ref().b.to_string() },
+///     }
+/// );
+/// ```
+///
+/// Leading `let` statements may be given before the fields, such as for an
+/// intermediate value used by several of them. They're run once each, in
+/// order, before any field is written, and their bindings are in scope for
+/// every field initializer.
+///
+/// ```rust
+/// # use place::place;
+/// # use std::mem::MaybeUninit;
+///
+/// struct Range {
+///     start: usize,
+///     end: usize,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut Range = place!(
+///     buf,
+///     Range {
+///         let len = "works".len();
+///         start: 10,
+///         end: 10 + len,
+///     }
+/// );
+/// assert_eq!(x.end, 15);
+/// ```
+///
+/// Fields which are themselves structs can be placed in-place too, by using
+/// `place!` without a buffer as the field value, such as
+/// `inner: place!(Inner { .. })`. This avoids ever building `Inner` by value.
+///
+/// Fields not given can be filled from another value with `..base`, such as
+/// `..Default::default()`, like struct update syntax. `base` is written to
+/// `buf` as a whole, after evaluating the given fields, which then replace
+/// their counterparts in `base`. Those are dropped, like with struct update
+/// syntax, and the rest are moved into `buf` without being copied or dropped.
+/// Unlike struct update syntax, `base` is always moved entirely.
+/// This can't be combined with nested `place!` fields.
+///
+/// ```rust
+/// # use place::place;
+/// # use std::mem::MaybeUninit;
+///
+/// #[derive(Default)]
+/// struct Config {
+///     name: String,
+///     verbose: bool,
+///     paths: Vec<String>,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut Config = place!(
+///     buf,
+///     Config {
+///         name: String::from("works"),
+///         ..Default::default()
+///     }
+/// );
+/// assert!(x.paths.is_empty());
+///
+/// # // SAFETY: buf has been initialized above
+/// # unsafe { buf.assume_init_drop() };
+/// ```
+///
+/// Fields are written without requiring them to be aligned, so
+/// `#[repr(packed)]` structs are supported. Nested `place!` and
+/// [`emplace_fn!`] fields need a reference to the field, so they're an error
+/// if it may be unaligned.
+///
+/// ```rust,compile_fail,E0793
+/// # use place::place;
+/// # use std::mem::MaybeUninit;
+/// struct Inner {
+///     a: u32,
+/// }
+///
+/// #[repr(C, packed)]
+/// struct Packed {
+///     tag: u8,
+///     inner: Inner,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut Packed = place!(
+///     buf,
+///     Packed {
+///         tag: 1,
+///         inner: place!(Inner { a: 5 }),
+///     }
+/// );
+/// ```
+///
+/// The type may be given as any path, such as `foo::Bar`, `Wrapper::<u32>`,
+/// or `Buf::<8>`. Generic parameters that aren't specified are inferred from
+/// the values and `buf`, like a struct literal, even those with defaults.
+/// The path can't be left out entirely and inferred from `buf`, as it's
+/// needed to check that all fields were given, but inside the type's `impl`
+/// it can be `Self`. It can also be a type alias, such as one gated on
+/// `#[cfg]` to choose between structs with the same fields. Macros passing
+/// the type on to `place!` should take it as an `ident` or `tt`s, as, like
+/// struct literals, it can't be a `path` fragment.
+///
+/// The type may also be given separately from its fields, as
+/// `place!(buf, Path, { .. })`, which is otherwise identical.
+///
+/// ```rust
+/// # use place::place;
+/// # use std::mem::MaybeUninit;
+///
+/// struct Wrapper<T> {
+///     value: T,
+///     len: usize,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x = place!(buf, Wrapper::<u64>, { value: 1, len: 1 });
+/// assert_eq!(x.value, 1u64);
+/// ```
+///
+/// Tuple structs, with up to 32 fields, are supported too, as are unit
+/// structs, which may be given as either `Unit` or `Unit {}`.
+///
+/// ```rust
+/// # use place::place;
+/// # use std::mem::MaybeUninit;
+///
+/// struct Rgb(u8, u8, u8);
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut Rgb = place!(buf, Rgb(255, 0, 255));
+/// ```
+///
+/// Enum variants are supported by prefixing them with `enum`, as a path alone
+/// can't tell a variant apart from a struct. Like structs, the variant may be
+/// given by any path, such as `enum crate::ast::Node::Binary { .. }`.
+///
+/// The fields of an enum variant can't be addressed independently of the
+/// rest of the enum, so the variant is constructed as a whole and then written
+/// to `buf`. Fields are still evaluated in order and dropped if an initializer
+/// panics, but the value may be built on the stack before being moved into
+/// `buf`.
+///
+/// ```rust
+/// # use place::place;
+/// # use std::mem::MaybeUninit;
+///
+/// enum Instr {
+///     Push { value: String },
+///     Jump(usize),
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut Instr = place!(
+///     buf,
+///     enum Instr::Push {
+///         value: String::from("works"),
+///     }
+/// );
+///
+/// # // SAFETY: buf has been initialized above
+/// # unsafe { buf.assume_init_drop() };
+///
+/// let x: &mut Instr = place!(buf, enum Instr::Jump(5));
+/// ```
 #[macro_export]
 macro_rules! place {
-    (
-        $buf:expr,
-        $typ:ident {
-            $(
-                $f:ident: $f_val:expr
-            ),*
-            $(,)?
-        }
-    ) => {{
-        use core::{mem::MaybeUninit, ptr::addr_of_mut};
-        const _: () = {
-            // Ignore useless warnings
-            #[allow(unreachable_code, clippy::diverging_sub_expression)]
-            fn _check_types() {
-                // This check means Rust will validate that all struct fields were passed in,
-                // meaning that all fields will be initialized below
-                //
-                // This check is the key to making this macro safe.
-                $typ {
-                    $(
-                        $f: loop {}
-                    ),*
-                };
-            }
-        };
-        // Ensures types are correct
-        let buf: &mut MaybeUninit<$typ> = &mut $buf;
-        let ptr = buf.as_mut_ptr();
-        $(
-            // SAFETY: Only pointers are used, and the above compile check
-            // ensures all fields were specified
-            unsafe { addr_of_mut!((*ptr).$f).write($f_val); }
-        )*
-        // SAFETY: All fields have been initialized above
-        // The compiler ensures that all fields were used, all types were correct,
-        // and that size and alignment are correct.
-        unsafe { buf.assume_init_mut() }
-    }};
+    ($buf:expr, $($t:tt)+) => {
+        $crate::__place!(__place_fields $buf, $($t)+)
+    };
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use core::mem::MaybeUninit;
+/// Initialize a struct in-place in `const` contexts, and return it by value
+///
+/// This works like [`place!`], except it uses its own buffer and returns the
+/// value, so it can be used for `const` and `static` items or in `const fn`.
+/// Field initializers must themselves be usable in `const`.
+///
+/// Placing enum variants requires Rust 1.85, everything else 1.83.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::const_place;
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     u: u32,
+/// }
+///
+/// const X: MyCoolStruct = const_place!(MyCoolStruct { b: true, u: 69420 });
+///
+/// const _: () = assert!(X.b && X.u == 69420);
+/// ```
+#[macro_export]
+macro_rules! const_place {
+    ($($t:tt)+) => {{
+        let mut buf = ::core::mem::MaybeUninit::uninit();
+        $crate::__place!(__place_fields_const buf, $($t)+);
+        // SAFETY: buf has been initialized above
+        #[allow(unused_unsafe)]
+        let init = unsafe { buf.assume_init() };
+        init
+    }};
+}
 
-    #[derive(Debug)]
-    struct MyCoolStruct {
-        b: bool,
-        s: String,
-        v: Vec<String>,
-    }
+/// Initialize a struct in-place, and return it by value
+///
+/// This works exactly like [`place!`], except it uses its own buffer, and
+/// returns the value once all fields have been written.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_value;
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// let x: MyCoolStruct = place_value!(MyCoolStruct {
+///     b: true,
+///     s: String::from("works"),
+/// });
+/// assert_eq!(x.s, "works");
+/// ```
+#[macro_export]
+macro_rules! place_value {
+    ($($t:tt)+) => {{
+        let mut buf = ::core::mem::MaybeUninit::uninit();
+        $crate::place!(buf, $($t)+);
+        // SAFETY: buf has been initialized above
+        #[allow(unused_unsafe)]
+        let init = unsafe { buf.assume_init() };
+        init
+    }};
+}
 
-    #[test]
-    fn miri() {
-        let mut buf = MaybeUninit::uninit();
+/// Initialize a struct in-place at `buf` with the default value of each of
+/// its fields, and return a mutable reference
+///
+/// This works exactly like [`place!`], with the fields listed by name only,
+/// and each written with [`Default::default`]. Unlike `..Default::default()`,
+/// the fields are each written directly, without building a default value
+/// of the whole struct first, and the struct itself needn't implement
+/// [`Default`].
+///
+/// As macros can't know the fields of a type, they must still all be listed,
+/// which is checked like [`place!`], unless it derives `PlaceDefault` for
+/// `emplace_default!`, with the `derive` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_default;
+/// # use std::mem::MaybeUninit;
+///
+/// struct Config {
+///     name: String,
+///     verbose: bool,
+///     paths: Vec<String>,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut Config = place_default!(buf, Config { name, verbose, paths });
+/// assert!(x.name.is_empty());
+///
+/// # // SAFETY: buf has been initialized above
+/// # unsafe { buf.assume_init_drop() };
+/// ```
+///
+/// [`place!`]: crate::place
+#[macro_export]
+macro_rules! place_default {
+    ($buf:expr, $($t:tt)+) => {
+        $crate::__place_path!(@placer __place_default $buf, [], $($t)+)
+    };
+}
 
-        let x: &mut MyCoolStruct = place!(
-            buf,
-            MyCoolStruct {
-                b: true,
-                s: String::from("works"),
-                v: vec![String::from("works")],
+/// Gives each field its default value
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_default {
+    ($buf:expr, [$($path:tt)+], $($(#[$attr:meta])* $f:ident),* $(,)?) => {
+        $crate::place!(
+            $buf,
+            $($path)+ {
+                $(
+                    $(#[$attr])*
+                    $f: ::core::default::Default::default()
+                ),*
             }
-        );
-        dbg!(x);
+        )
+    };
+}
 
-        // SAFETY: buf has been initialized above
-        unsafe { buf.assume_init_drop() };
+/// Initialize a struct in-place at `buf`, with its fields given as
+/// statements, and return a mutable reference
+///
+/// This works exactly like [`place!`], except each field is written as
+/// `field = value;` rather than `field: value,`, which can be simpler for code
+/// generators to emit. The final `;` is optional. Fields may have attributes,
+/// and be nested `place!` or [`emplace_fn!`] fields, but `..base` and shorthand
+/// fields aren't supported.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_stmts;
+/// # use std::mem::MaybeUninit;
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut MyCoolStruct = place_stmts!(
+///     buf,
+///     MyCoolStruct {
+///         b = true;
+///         s = String::from("works");
+///     }
+/// );
+/// assert_eq!(x.s, "works");
+///
+/// # // SAFETY: buf has been initialized above
+/// # unsafe { buf.assume_init_drop() };
+/// ```
+///
+/// [`place!`]: crate::place
+/// [`emplace_fn!`]: crate::emplace_fn
+#[macro_export]
+macro_rules! place_stmts {
+    ($buf:expr, $($t:tt)+) => {
+        $crate::__place_path!(@placer __place_stmts $buf, [], $($t)+)
+    };
+}
+
+/// Rewrites each statement as a field, keeping nested fields as tokens so
+/// `place!` still recognizes them
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_stmts {
+    (
+        @munch $buf:expr,
+        [$($path:tt)+],
+        [$($done:tt)*],
+        $(#[$attr:meta])* $f:ident = place!($($inner:tt)+) $(; $($rest:tt)*)?
+    ) => {
+        $crate::__place_stmts!(
+            @munch $buf,
+            [$($path)+],
+            [$($done)* $(#[$attr])* $f: place!($($inner)+),],
+            $($($rest)*)?
+        )
+    };
+
+    (
+        @munch $buf:expr,
+        [$($path:tt)+],
+        [$($done:tt)*],
+        $(#[$attr:meta])* $f:ident = emplace_fn!($($inner:tt)+) $(; $($rest:tt)*)?
+    ) => {
+        $crate::__place_stmts!(
+            @munch $buf,
+            [$($path)+],
+            [$($done)* $(#[$attr])* $f: emplace_fn!($($inner)+),],
+            $($($rest)*)?
+        )
+    };
+
+    (
+        @munch $buf:expr,
+        [$($path:tt)+],
+        [$($done:tt)*],
+        $(#[$attr:meta])* $f:ident = $f_val:expr $(; $($rest:tt)*)?
+    ) => {
+        $crate::__place_stmts!(
+            @munch $buf,
+            [$($path)+],
+            [$($done)* $(#[$attr])* $f: $f_val,],
+            $($($rest)*)?
+        )
+    };
+
+    (@munch $buf:expr, [$($path:tt)+], [$($done:tt)*],) => {
+        $crate::place!($buf, $($path)+ { $($done)* })
+    };
+
+    ($buf:expr, [$($path:tt)+], $($t:tt)*) => {
+        $crate::__place_stmts!(@munch $buf, [$($path)+], [], $($t)*)
+    };
+}
+
+/// Drop the value behind the mutable reference `r`, and initialize a new one
+/// in-place, returning `r`
+///
+/// This works exactly like [`place!`], except `r` is a `&mut T` to an
+/// initialized value, which is dropped before any field initializers are
+/// evaluated. The value is never moved.
+///
+/// # Aborts
+///
+/// If dropping the old value or a field initializer panics, as the value
+/// behind `r` would be left uninitialized.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::reinit;
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// let mut x = MyCoolStruct {
+///     b: false,
+///     s: String::from("old"),
+/// };
+///
+/// reinit!(
+///     &mut x,
+///     MyCoolStruct {
+///         b: true,
+///         s: String::from("works"),
+///     }
+/// );
+/// assert_eq!(x.s, "works");
+/// ```
+#[macro_export]
+macro_rules! reinit {
+    ($r:expr, $($t:tt)+) => {{
+        let r: &mut _ = $r;
+        let guard = $crate::__private::AbortOnUnwind;
+        // SAFETY: The value is initialized again below, and guard aborts
+        // before anything can see it uninitialized
+        #[allow(unused_unsafe)]
+        let buf = unsafe { $crate::__private::drop_uninit(r) };
+        let r = $crate::place!(buf, $($t)+);
+        ::core::mem::forget(guard);
+        r
+    }};
+}
+
+/// Replace the value behind the mutable reference `r` with a new one
+/// initialized in-place, returning the old value
+///
+/// This works like [`mem::replace`], except the new value is initialized
+/// exactly like [`place!`], directly in `r`. The old value is moved out
+/// before any field initializers are evaluated.
+///
+/// If a field initializer panics, the fields already written are dropped, and
+/// the old value is moved back, so `r` is left unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_replace;
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// let mut x = MyCoolStruct {
+///     b: false,
+///     s: String::from("old"),
+/// };
+///
+/// let old: MyCoolStruct = place_replace!(
+///     &mut x,
+///     MyCoolStruct {
+///         b: true,
+///         s: String::from("works"),
+///     }
+/// );
+/// assert_eq!(old.s, "old");
+/// assert_eq!(x.s, "works");
+/// ```
+///
+/// [`mem::replace`]: core::mem::replace
+#[macro_export]
+macro_rules! place_replace {
+    ($r:expr, $($t:tt)+) => {{
+        let mut replace = $crate::__private::Replace::new($r);
+        $crate::place!(replace.buf(), $($t)+);
+        // SAFETY: buf has been initialized above
+        #[allow(unused_unsafe)]
+        let old = unsafe { replace.finish() };
+        old
+    }};
+}
+
+/// Replace a single field of the value behind the mutable reference `r`,
+/// returning the old value of the field
+///
+/// The other fields aren't touched, and the value isn't moved. `field` is a
+/// field name or tuple index, which is checked to exist like any field
+/// access. `value` is evaluated before anything is replaced, so if it panics
+/// `r` is left unchanged.
+///
+/// This is [`mem::replace`] on the field. To initialize the new value of a
+/// struct field in-place instead, use [`place_replace!`] on it, as
+/// `place_replace!(&mut r.field, ..)`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::replace_field;
+///
+/// struct State {
+///     name: String,
+///     items: Vec<u32>,
+/// }
+///
+/// let mut x = State {
+///     name: String::from("old"),
+///     items: vec![1, 2, 3],
+/// };
+///
+/// let old: String = replace_field!(&mut x, name, String::from("works"));
+/// assert_eq!(old, "old");
+/// assert_eq!(x.name, "works");
+/// assert_eq!(x.items, [1, 2, 3]);
+/// ```
+///
+/// [`mem::replace`]: core::mem::replace
+#[macro_export]
+macro_rules! replace_field {
+    ($r:expr, $f:tt, $value:expr $(,)?) => {{
+        let r: &mut _ = $r;
+        let value = $value;
+        ::core::mem::replace(&mut r.$f, value)
+    }};
+}
+
+/// Initialize a struct in-place at `buf`, and return it pinned
+///
+/// This works exactly like [`place!`], returning a [`Pin<&mut T>`][Pin]
+/// instead. The fields are written through pointers directly into `buf`, so the
+/// value is never moved.
+///
+/// As this is unsafe, `buf` is given as an `unsafe` block, such as
+/// `unsafe { &mut buf }`, rather than wrapping the whole invocation in one.
+/// Only the block is evaluated in an unsafe context, so field initializers
+/// still need their own `unsafe` blocks to perform unsafe operations.
+///
+/// # Safety
+///
+/// The value in `buf` must never be moved out of or invalidated until it has
+/// been dropped in place, such as with [`MaybeUninit::assume_init_drop`].
+///
+/// This includes overwriting or reusing `buf`, or letting it go out of scope,
+/// without first dropping the value.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_pin;
+/// # use std::{marker::PhantomPinned, mem::MaybeUninit, pin::Pin};
+///
+/// struct Node {
+///     value: u32,
+///     _pin: PhantomPinned,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: Pin<&mut Node> = place_pin!(
+///     // SAFETY: The value is dropped in place below, before buf goes away
+///     unsafe { &mut buf },
+///     Node {
+///         value: 69420,
+///         _pin: PhantomPinned,
+///     }
+/// );
+///
+/// // SAFETY: buf has been initialized above
+/// unsafe { buf.assume_init_drop() };
+/// ```
+///
+/// Field initializers aren't in the `unsafe` block
+///
+/// ```rust,compile_fail,E0133
+/// # use place::place_pin;
+/// # use std::{marker::PhantomPinned, mem::MaybeUninit};
+/// # struct Node {
+/// #     value: u32,
+/// #     _pin: PhantomPinned,
+/// # }
+/// unsafe fn answer() -> u32 {
+///     69420
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x = place_pin!(
+///     unsafe { &mut buf },
+///     Node {
+///         value: answer(),
+///         _pin: PhantomPinned,
+///     }
+/// );
+/// ```
+///
+/// [Pin]: core::pin::Pin
+/// [`MaybeUninit::assume_init_drop`]: core::mem::MaybeUninit::assume_init_drop
+#[macro_export]
+macro_rules! place_pin {
+    ($unsafe:tt $buf:tt, $($t:tt)+) => {{
+        $crate::__place_unsafe!($unsafe);
+        #[allow(unused_unsafe)]
+        let buf = $unsafe $buf;
+        let init = $crate::place!(buf, $($t)+);
+        // SAFETY: The caller's `unsafe` block ensures the value is never moved
+        #[allow(unused_unsafe)]
+        let pin = unsafe { ::core::pin::Pin::new_unchecked(init) };
+        pin
+    }};
+}
+
+/// Checks that an unsafe macro was given an `unsafe` block
+///
+/// The block is the caller's own tokens, so it's subject to their lints, such
+/// as `#![forbid(unsafe_code)]`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_unsafe {
+    (unsafe) => {};
+
+    ($($t:tt)*) => {
+        ::core::compile_error!(::core::concat!(
+            "expected an `unsafe` block giving the buffer, found `",
+            ::core::stringify!($($t)*),
+            "`"
+        ))
+    };
+}
+
+/// Initialize a struct in-place at the raw pointer `ptr`, and return a mutable
+/// reference
+///
+/// This works exactly like [`place!`], except it writes through a `*mut T`
+/// instead of a [`MaybeUninit`]. Like [`place_pin!`], `ptr` is given as an
+/// `unsafe` block, and field initializers aren't evaluated in it.
+///
+/// # Safety
+///
+/// `ptr` must be
+///
+/// - Non-null and valid for writes of `T`
+/// - Properly aligned for `T`
+/// - Not accessed through any other pointer or reference for the lifetime of
+///   the returned reference, which is chosen by the caller
+///
+/// The memory at `ptr` should be uninitialized, any existing value will be
+/// overwritten without being dropped.
+///
+/// It is your responsibility to drop your type if needed when you're done with
+/// it.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_ptr;
+/// # use std::alloc::{alloc, dealloc, Layout};
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// let layout = Layout::new::<MyCoolStruct>();
+/// // SAFETY: MyCoolStruct is not zero sized
+/// let ptr = unsafe { alloc(layout) }.cast::<MyCoolStruct>();
+/// assert!(!ptr.is_null());
+///
+/// let x: &mut MyCoolStruct = place_ptr!(
+///     // SAFETY: ptr was allocated above for MyCoolStruct
+///     unsafe { ptr },
+///     MyCoolStruct {
+///         b: true,
+///         s: String::from("works"),
+///     }
+/// );
+///
+/// // SAFETY: ptr has been initialized above, and was allocated with layout
+/// unsafe {
+///     ptr.drop_in_place();
+///     dealloc(ptr.cast(), layout);
+/// }
+/// ```
+///
+/// The `unsafe` block is your own, so `#![forbid(unsafe_code)]` applies to it
+///
+/// ```rust,compile_fail
+/// #![forbid(unsafe_code)]
+/// # use place::place_ptr;
+/// # struct MyCoolStruct {
+/// #     b: bool,
+/// # }
+/// # let ptr: *mut MyCoolStruct = std::ptr::null_mut();
+/// place_ptr!(unsafe { ptr }, MyCoolStruct { b: true });
+/// ```
+///
+/// [`MaybeUninit`]: core::mem::MaybeUninit
+/// [`place_pin!`]: crate::place_pin
+#[macro_export]
+macro_rules! place_ptr {
+    ($unsafe:tt $ptr:tt, $($t:tt)+) => {{
+        $crate::__place_unsafe!($unsafe);
+        #[allow(unused_unsafe)]
+        let ptr = $unsafe $ptr;
+        // SAFETY: The caller's `unsafe` block ensures ptr is valid
+        #[allow(unused_unsafe)]
+        let buf = unsafe { $crate::__private::uninit_mut(ptr) };
+        $crate::place!(buf, $($t)+)
+    }};
+}
+
+/// Initialize a struct in-place at the pointer `ptr`, and return a mutable
+/// reference
+///
+/// This works exactly like [`place_ptr!`], except it writes through a
+/// [`NonNull<T>`] instead of a `*mut T`.
+///
+/// # Safety
+///
+/// `ptr` must be
+///
+/// - Valid for writes of `T`
+/// - Properly aligned for `T`
+/// - Not accessed through any other pointer or reference for the lifetime of
+///   the returned reference, which is chosen by the caller
+///
+/// The memory at `ptr` should be uninitialized, any existing value will be
+/// overwritten without being dropped.
+///
+/// It is your responsibility to drop your type if needed when you're done with
+/// it.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_nonnull;
+/// # use std::{alloc::{alloc, dealloc, handle_alloc_error, Layout}, ptr::NonNull};
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// let layout = Layout::new::<MyCoolStruct>();
+/// // SAFETY: MyCoolStruct is not zero sized
+/// let ptr = unsafe { alloc(layout) }.cast::<MyCoolStruct>();
+/// let ptr = NonNull::new(ptr).unwrap_or_else(|| handle_alloc_error(layout));
+///
+/// let x: &mut MyCoolStruct = place_nonnull!(
+///     // SAFETY: ptr was allocated above for MyCoolStruct
+///     unsafe { ptr },
+///     MyCoolStruct {
+///         b: true,
+///         s: String::from("works"),
+///     }
+/// );
+///
+/// // SAFETY: ptr has been initialized above, and was allocated with layout
+/// unsafe {
+///     ptr.drop_in_place();
+///     dealloc(ptr.as_ptr().cast(), layout);
+/// }
+/// ```
+///
+/// [`NonNull<T>`]: core::ptr::NonNull
+/// [`place_ptr!`]: crate::place_ptr
+#[macro_export]
+macro_rules! place_nonnull {
+    ($unsafe:tt $ptr:tt, $($t:tt)+) => {{
+        $crate::__place_unsafe!($unsafe);
+        #[allow(unused_unsafe)]
+        let ptr: ::core::ptr::NonNull<_> = $unsafe $ptr;
+        $crate::place_ptr!(unsafe { ptr.as_ptr() }, $($t)+)
+    }};
+}
+
+/// Write only some fields of a struct in-place at `buf`, leaving the rest
+/// uninitialized, and return a raw pointer to it
+///
+/// This is the escape hatch [`place!`] exists to avoid. The fields listed
+/// are written like [`place!`], in order, and the trailing `..` says every
+/// other field is deliberately left alone, such as for C code to initialize.
+/// Nothing checks that the others are ever initialized, so this returns a
+/// `*mut T` rather than a reference.
+///
+/// Field names and types are still checked, and if an initializer panics,
+/// the fields already written are dropped. Nested `place!` fields and
+/// `..base` are not supported.
+///
+/// Like [`place_pin!`], `buf` is given as an `unsafe` block, and field
+/// initializers aren't evaluated in it.
+///
+/// # Safety
+///
+/// **The value is not initialized.** Until every other field has been
+/// initialized, it is undefined behavior to
+///
+/// - Create a reference to the value, or to any field not listed
+/// - Read the value, or any field not listed
+/// - Drop the value, or call [`MaybeUninit::assume_init`] or similar on `buf`
+///
+/// Only the fields listed may be accessed, through the returned pointer, such
+/// as with [`addr_of!`]. Padding, and fields of types with no invalid values,
+/// are still uninitialized, and reading them is undefined behavior too.
+///
+/// It is your responsibility to drop the fields written if needed.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_partial;
+/// # use std::{mem::MaybeUninit, ptr::addr_of_mut};
+///
+/// #[repr(C)]
+/// struct Request {
+///     kind: u32,
+///     // Filled in by the C side
+///     reply: [u8; 16],
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let ptr: *mut Request = place_partial!(
+///     // SAFETY: Only the fields written are accessed until `reply` is
+///     unsafe { &mut buf },
+///     Request { kind: 1, .. }
+/// );
+///
+/// // SAFETY: ptr points to buf, and `reply` is the only field left
+/// let x: &mut Request = unsafe {
+///     addr_of_mut!((*ptr).reply).write([0; 16]);
+///     buf.assume_init_mut()
+/// };
+/// assert_eq!(x.kind, 1);
+/// ```
+///
+/// [`place!`]: crate::place
+/// [`place_pin!`]: crate::place_pin
+/// [`MaybeUninit::assume_init`]: core::mem::MaybeUninit::assume_init
+/// [`addr_of!`]: core::ptr::addr_of
+#[macro_export]
+macro_rules! place_partial {
+    ($unsafe:tt $buf:tt, $($t:tt)+) => {{
+        $crate::__place_unsafe!($unsafe);
+        #[allow(unused_unsafe)]
+        let buf = $unsafe $buf;
+        $crate::__place_path!(@placer __place_partial buf, [], $($t)+)
+    }};
+}
+
+/// Writes the fields given, ignoring the rest
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_partial {
+    ($buf:expr, [$($path:tt)+], $($f:ident: $f_val:expr,)* ..) => {{
+        use $crate::__private::AsPlace as _;
+        let buf = $buf.__as_place();
+        let ptr = $crate::Place::as_uninit_ptr(buf);
+        // Unlike `check`, a pattern only needs the fields given, and still
+        // infers the type.
+        $crate::__private::check_dst(ptr, |v| {
+            let $($path)+ { $($f: _,)* .. } = *v;
+        });
+        // See `__place_fields`
+        #[allow(unused_unsafe, unused_mut)]
+        let mut guard = $crate::__private::DropGuard::new(ptr, &[
+            $(
+                |ptr| {
+                    // SAFETY: The guard only calls this for fields that have
+                    // been written
+                    unsafe { $crate::__private::drop_field(::core::ptr::addr_of_mut!((*ptr).$f)) }
+                }
+            ),*
+        ]);
+        $(
+            // SAFETY: ptr points to the value being placed, so the field is
+            // in bounds. Nothing is read.
+            #[allow(unused_unsafe)]
+            let field = unsafe { ::core::ptr::addr_of_mut!((*ptr).$f) };
+            let val = $crate::__private::field_value(field, $f_val);
+            // SAFETY: buf is valid for writes of the value, and the caller's
+            // `unsafe` block deals with the rest of the fields
+            #[allow(unused_unsafe)]
+            unsafe {
+                ::core::ptr::write_unaligned(field, val)
+            };
+            guard.written();
+        )*
+        ::core::mem::forget(guard);
+        ptr
+    }};
+}
+
+/// Initialize a struct in-place at `buf` after zeroing it, and return a
+/// mutable reference
+///
+/// This works exactly like [`place!`], except every byte of `buf` is first
+/// set to zero, so the padding between fields is zero rather than
+/// uninitialized. This makes `#[repr(C)]` structs safe to read as bytes, such
+/// as to pass to C or serialize.
+///
+/// Padding inside fields written by value is copied from the value, and may
+/// still be uninitialized. Using nested `place!` fields for those avoids this.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_zeroed;
+/// # use std::{mem::{size_of, MaybeUninit}, slice};
+///
+/// #[repr(C)]
+/// struct Header {
+///     kind: u8,
+///     len: u32,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut Header = place_zeroed!(buf, Header { kind: 1, len: 8 });
+///
+/// let ptr = (x as *const Header).cast::<u8>();
+/// // SAFETY: Every byte of x is initialized, including padding
+/// let bytes = unsafe { slice::from_raw_parts(ptr, size_of::<Header>()) };
+/// assert_eq!(bytes[..4], [1, 0, 0, 0]);
+/// ```
+///
+/// [`place!`]: crate::place
+#[macro_export]
+macro_rules! place_zeroed {
+    ($buf:expr, $($t:tt)+) => {{
+        use $crate::__private::AsPlace as _;
+        let buf: &mut ::core::mem::MaybeUninit<_> = $buf.__as_place();
+        $crate::__private::zero(buf);
+        $crate::place!(buf, $($t)+)
+    }};
+}
+
+/// Initialize a value in-place at `buf` by calling the function `init` with
+/// it, and return a mutable reference
+///
+/// Rust never guarantees that a value returned from a function by value isn't
+/// copied on the way to where it's stored. [`place!`] avoids ever building the
+/// whole struct by value, but each field initializer still produces its value
+/// by value, which is then moved into place, and may be copied by doing so.
+/// Nested `place!` fields avoid this by being placed field by field too.
+///
+/// This is for when a value has to be produced by a function, such as a
+/// constructor. Instead of returning it, `init` is given
+/// `&mut MaybeUninit<T>` to initialize, and returns the reference to it, such
+/// as from [`MaybeUninit::write`], or [`place!`]. Whatever `init` writes to
+/// `buf` is never copied or moved.
+///
+/// It may also be used as the value of a field, without a buffer, such as
+/// `inner: emplace_fn!(Inner::init)`, to initialize that field with `init`.
+///
+/// # Panics
+///
+/// If `init` returns a reference to anything but `buf`
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{emplace_fn, place};
+/// # use std::mem::MaybeUninit;
+///
+/// struct Table {
+///     id: u32,
+///     rows: [u64; 512],
+/// }
+///
+/// impl Table {
+///     fn init(buf: &mut MaybeUninit<Self>) -> &mut Self {
+///         place!(buf, Table { id: 1, rows: [0; 512] })
+///     }
+/// }
+///
+/// struct Database {
+///     name: &'static str,
+///     table: Table,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut Database = place!(
+///     buf,
+///     Database {
+///         name: "works",
+///         table: emplace_fn!(Table::init),
+///     }
+/// );
+/// assert_eq!(x.table.id, 1);
+///
+/// let mut buf = MaybeUninit::uninit();
+/// let x: &mut Table = emplace_fn!(buf, Table::init);
+/// ```
+///
+/// [`MaybeUninit::write`]: core::mem::MaybeUninit::write
+#[macro_export]
+macro_rules! emplace_fn {
+    ($buf:expr, $init:expr $(,)?) => {{
+        use $crate::__private::AsPlace as _;
+        $crate::__private::emplace_fn($buf.__as_place(), $init)
+    }};
+}
+
+/// Initialize a value in-place at `buf` by calling `init` with a pointer to
+/// it, and return a mutable reference
+///
+/// This is for initialization that can't be written as one expression per
+/// field, such as fields that point to each other. `init` is called with a
+/// `*mut T` to the uninitialized value, and may write the fields in any way,
+/// such as through [`addr_of_mut!`].
+///
+/// `buf` may be anything accepted by [`place!`].
+///
+/// # Safety
+///
+/// `init` must fully initialize the value at the pointer before returning.
+///
+/// If `init` panics, `buf` must be treated as uninitialized, and anything it
+/// has written is leaked.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_with;
+/// # use std::{mem::MaybeUninit, ptr::{addr_of, addr_of_mut}};
+///
+/// struct SelfRef {
+///     data: u32,
+///     this: *const u32,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// // SAFETY: Every field is written below
+/// let x: &mut SelfRef = unsafe {
+///     place_with!(buf, SelfRef, |p: *mut SelfRef| {
+///         addr_of_mut!((*p).data).write(69420);
+///         addr_of_mut!((*p).this).write(addr_of!((*p).data));
+///     })
+/// };
+/// // SAFETY: x.this points to x.data
+/// assert_eq!(unsafe { *x.this }, 69420);
+/// ```
+///
+/// [`addr_of_mut!`]: core::ptr::addr_of_mut
+#[macro_export]
+macro_rules! place_with {
+    ($buf:expr, $typ:ty, $init:expr $(,)?) => {{
+        use $crate::__private::AsPlace as _;
+        let buf = $buf.__as_place();
+        $crate::__private::place_with::<$typ, _>(buf, $init)
+    }};
+}
+
+/// Initialize a struct in-place in a shared [`UnsafeCell`], and return a raw
+/// pointer to it
+///
+/// This works exactly like [`place!`], except `cell` is a
+/// `&UnsafeCell<MaybeUninit<T>>`, such as storage in a `static`. As only a
+/// shared reference is needed, a `*mut T` is returned instead of a reference.
+/// Like [`place_pin!`], `cell` is given as an `unsafe` block, and field
+/// initializers aren't evaluated in it.
+///
+/// # Safety
+///
+/// Nothing else may access the value in `cell` while it's being initialized,
+/// such as from another thread, or reentrantly from a field initializer.
+/// Initializing it must also happen before, and be synchronized with, any
+/// reads of it.
+///
+/// The value in `cell` should be uninitialized, any existing value will be
+/// overwritten without being dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_cell;
+/// # use std::{cell::UnsafeCell, mem::MaybeUninit};
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     u: u32,
+/// }
+///
+/// let cell = UnsafeCell::new(MaybeUninit::uninit());
+///
+/// let x: *mut MyCoolStruct = place_cell!(
+///     // SAFETY: Nothing else has access to cell
+///     unsafe { &cell },
+///     MyCoolStruct { b: true, u: 69420 }
+/// );
+/// // SAFETY: x was initialized above, and nothing else accesses it
+/// assert_eq!(unsafe { (*x).u }, 69420);
+/// ```
+///
+/// [`UnsafeCell`]: core::cell::UnsafeCell
+/// [`place_pin!`]: crate::place_pin
+#[macro_export]
+macro_rules! place_cell {
+    ($unsafe:tt $cell:tt, $($t:tt)+) => {{
+        $crate::__place_unsafe!($unsafe);
+        #[allow(unused_unsafe)]
+        let ptr = $crate::__private::cell_ptr($unsafe $cell);
+        $crate::place_ptr!(unsafe { ptr }, $($t)+);
+        ptr
+    }};
+}
+
+/// Initialize a struct in-place in a `static mut`, and return a `'static`
+/// mutable reference
+///
+/// This works exactly like [`place!`], for `static mut` storage of a
+/// [`MaybeUninit`], such as a singleton initialized once at startup. It's
+/// given a raw pointer to the static, such as from [`addr_of_mut!`], so no
+/// reference to it is ever created, and returns `&'static mut T`. Like
+/// [`place_pin!`], the pointer is given as an `unsafe` block, and field
+/// initializers aren't evaluated in it.
+///
+/// A `&'static mut MaybeUninit<T>` obtained some other way can be given to
+/// `place!` as is, which returns a `&'static mut T` without needing `unsafe`.
+///
+/// # Safety
+///
+/// `storage` must point to a `static mut`, and this must only be called once
+/// for it, or again only once the returned reference is no longer used.
+/// Nothing else may access it while the returned reference is used, such as
+/// from another thread, which usually means guarding it with something like
+/// `std::sync::Once`.
+///
+/// The value in `storage` should be uninitialized, any existing value will be
+/// overwritten without being dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_static;
+/// # use std::{mem::MaybeUninit, ptr::addr_of_mut, sync::Once};
+///
+/// struct Device {
+///     id: u32,
+///     name: &'static str,
+/// }
+///
+/// static mut DEVICE: MaybeUninit<Device> = MaybeUninit::uninit();
+/// static INIT: Once = Once::new();
+///
+/// INIT.call_once(|| {
+///     let x: &'static mut Device = place_static!(
+///         // SAFETY: DEVICE is a static, and INIT ensures it's only
+///         // initialized once
+///         unsafe { addr_of_mut!(DEVICE) },
+///         Device { id: 1, name: "works" }
+///     );
+///     assert_eq!(x.name, "works");
+/// });
+/// ```
+///
+/// [`MaybeUninit`]: core::mem::MaybeUninit
+/// [`addr_of_mut!`]: core::ptr::addr_of_mut
+/// [`place_pin!`]: crate::place_pin
+#[macro_export]
+macro_rules! place_static {
+    ($unsafe:tt $storage:tt, $($t:tt)+) => {{
+        $crate::__place_unsafe!($unsafe);
+        #[allow(unused_unsafe)]
+        let storage = $unsafe $storage;
+        // SAFETY: The caller's `unsafe` block ensures storage is a static,
+        // which nothing else accesses
+        #[allow(unused_unsafe)]
+        let buf = unsafe { $crate::__private::static_mut(storage) };
+        $crate::place!(buf, $($t)+)
+    }};
+}
+
+/// Move the value out of `buf`, leaving it uninitialized
+///
+/// This is the other end of [`place!`], like [`MaybeUninit::assume_init_read`]
+/// for any [`Place`], so a value can be placed, used in place, and then taken
+/// by value. `buf` can be placed into again afterward.
+///
+/// With debug assertions enabled, every byte of `buf` is overwritten after the
+/// value is read, so that accidentally using the old value again is more
+/// likely to fail loudly rather than appear to work.
+///
+/// # Safety
+///
+/// `buf` must be initialized. Afterward it must not be read or dropped, such
+/// as with [`MaybeUninit::assume_init_drop`], until a new value is placed
+/// into it, as the taken value owns everything the old one did.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{place, take};
+/// # use std::mem::MaybeUninit;
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut MyCoolStruct = place!(
+///     buf,
+///     MyCoolStruct {
+///         b: true,
+///         s: String::from("works"),
+///     }
+/// );
+/// x.s.push_str(" well");
+///
+/// // SAFETY: buf has been initialized above, and isn't used again
+/// let x: MyCoolStruct = unsafe { take!(buf) };
+/// assert_eq!(x.s, "works well");
+/// ```
+///
+/// [`MaybeUninit`]: core::mem::MaybeUninit
+/// [`MaybeUninit::assume_init_read`]: core::mem::MaybeUninit::assume_init_read
+/// [`MaybeUninit::assume_init_drop`]: core::mem::MaybeUninit::assume_init_drop
+#[macro_export]
+macro_rules! take {
+    ($buf:expr $(,)?) => {{
+        use $crate::__private::AsPlace as _;
+        $crate::__private::take($buf.__as_place())
+    }};
+}
+
+/// Initialize a struct in-place at `buf`, and return a raw pointer to it
+///
+/// This works exactly like [`place!`], except a `*mut T` is returned, without
+/// ever creating a reference to the whole value. A `&mut T` asserts that
+/// nothing else can access the value for as long as it, or anything derived
+/// from it, is used, so a pointer derived from one can be invalidated by any
+/// other access. This is for protocols that publish the pointer once the value
+/// is initialized, such as with an atomic store, for others to access through
+/// their own references.
+///
+/// `..base` isn't supported, as `base` is written as a whole.
+///
+/// It is your responsibility to drop your type if needed when you're done with
+/// it, and to only use the pointer for as long as `buf` is valid.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_raw;
+/// # use std::{mem::MaybeUninit, ptr, sync::atomic::{AtomicPtr, Ordering}};
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// static PUBLISHED: AtomicPtr<MyCoolStruct> = AtomicPtr::new(ptr::null_mut());
+///
+/// let buf = Box::leak(Box::new(MaybeUninit::uninit()));
+/// let x: *mut MyCoolStruct = place_raw!(
+///     buf,
+///     MyCoolStruct {
+///         b: true,
+///         s: String::from("works"),
+///     }
+/// );
+/// PUBLISHED.store(x, Ordering::Release);
+///
+/// let x = PUBLISHED.load(Ordering::Acquire);
+/// // SAFETY: x was initialized before being published, and is never written
+/// assert_eq!(unsafe { &(*x).s }, "works");
+/// ```
+#[macro_export]
+macro_rules! place_raw {
+    ($buf:expr, $($t:tt)+) => {
+        $crate::__place!(__place_fields_raw $buf, $($t)+)
+    };
+}
+
+/// Initialize a struct in-place at `buf` from fallible initializers,
+/// returning a mutable reference
+///
+/// This works exactly like [`place!`], except field initializers may use `?`.
+/// On the first error, the fields already written are dropped and the error
+/// is returned, leaving `buf` uninitialized.
+///
+/// `buf` may be any [`Place`], like with `place!`.
+///
+/// The field initializers are run inside a closure, which changes how some
+/// expressions in them behave compared to `place!`:
+///
+/// - `?` and `return` exit `try_place!`, not the surrounding function, so
+///   `return Err(e)` fails like `?` does. An error from `?` must convert into
+///   the error type of `try_place!`, not that of the function.
+/// - `break` and `continue` can't refer to loops outside of `try_place!`.
+/// - `.await` can't be used, even in an `async` function.
+///
+/// ```rust
+/// # use place::try_place;
+/// # use std::mem::MaybeUninit;
+/// # struct MyCoolStruct {
+/// #     s: String,
+/// #     u: u32,
+/// # }
+/// fn parse(s: &str) -> Option<u32> {
+///     let mut buf = MaybeUninit::uninit();
+///
+///     let x: Result<&mut MyCoolStruct, &str> = try_place!(
+///         buf,
+///         MyCoolStruct {
+///             s: String::from(s),
+///             u: match s.parse() {
+///                 Ok(u) => u,
+///                 // Only exits `try_place!`
+///                 Err(_) => return Err("not a number"),
+///             },
+///         }
+///     );
+///     assert_eq!(x.err(), Some("not a number"));
+///     None
+/// }
+/// assert_eq!(parse("one"), None);
+/// ```
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::try_place;
+/// # use std::{mem::MaybeUninit, num::ParseIntError};
+///
+/// struct MyCoolStruct {
+///     s: String,
+///     u: u32,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: Result<&mut MyCoolStruct, ParseIntError> = try_place!(
+///     buf,
+///     MyCoolStruct {
+///         s: String::from("works"),
+///         u: "69420".parse()?,
+///     }
+/// );
+/// assert_eq!(x.unwrap().u, 69420);
+///
+/// # // SAFETY: buf has been initialized above
+/// # unsafe { buf.assume_init_drop() };
+/// ```
+///
+/// Prefixing the type with `indexed`, like `enum`, also returns the index of
+/// the field that failed along with the error, as `Result<&mut T, (usize, E)>`.
+/// Fields are counted in the order they're written, skipping any disabled by
+/// `#[cfg(..)]`. This isn't supported for enum variants, or with `..base`.
+///
+/// ```rust
+/// # use place::try_place;
+/// # use std::{mem::MaybeUninit, num::ParseIntError};
+/// # struct MyCoolStruct {
+/// #     s: String,
+/// #     u: u32,
+/// # }
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: Result<&mut MyCoolStruct, (usize, ParseIntError)> = try_place!(
+///     buf,
+///     indexed MyCoolStruct {
+///         s: String::from("works"),
+///         u: "not a number".parse()?,
+///     }
+/// );
+/// assert!(matches!(x, Err((1, _))));
+/// ```
+#[macro_export]
+macro_rules! try_place {
+    (@plain $buf:expr, $($t:tt)+) => {{
+        use $crate::__private::AsPlace as _;
+        let buf = $buf.__as_place();
+        (|| -> ::core::result::Result<_, _> {
+            // Move the reference in, so it can be returned
+            let buf = buf;
+            ::core::result::Result::Ok($crate::place!(*buf, $($t)+))
+        })()
+    }};
+
+    // A module named `indexed`
+    ($buf:expr, indexed :: $($t:tt)+) => {
+        $crate::try_place!(@plain $buf, indexed :: $($t)+)
+    };
+
+    ($buf:expr, indexed $($t:tt)+) => {{
+        use $crate::__private::AsPlace as _;
+        let buf = $buf.__as_place();
+        let index = ::core::cell::Cell::new(0);
+        let res = (|| -> ::core::result::Result<_, _> {
+            // See above
+            let buf = buf;
+            ::core::result::Result::Ok($crate::__place!(__place_fields_indexed (buf, &index), $($t)+))
+        })();
+        res.map_err(|e| (index.get(), e))
+    }};
+
+    ($buf:expr, $($t:tt)+) => {
+        $crate::try_place!(@plain $buf, $($t)+)
+    };
+}
+
+/// Initialize a struct in-place at `slice[idx]`, and return a mutable reference
+///
+/// This works exactly like [`place!`], with `slice` being a slice of
+/// [`MaybeUninit`].
+///
+/// The slot at `idx` should be uninitialized, any existing value will be
+/// overwritten without being dropped.
+///
+/// # Panics
+///
+/// If `idx` is out of bounds
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_slice;
+/// # use std::mem::MaybeUninit;
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// let mut slots: [MaybeUninit<MyCoolStruct>; 3] = [const { MaybeUninit::uninit() }; 3];
+///
+/// let x: &mut MyCoolStruct = place_slice!(
+///     slots,
+///     1,
+///     MyCoolStruct {
+///         b: true,
+///         s: String::from("works"),
+///     }
+/// );
+///
+/// # // SAFETY: slots[1] has been initialized above
+/// # unsafe { slots[1].assume_init_drop() };
+/// ```
+///
+/// [`MaybeUninit`]: core::mem::MaybeUninit
+#[macro_export]
+macro_rules! place_slice {
+    ($slice:expr, $idx:expr, $($t:tt)+) => {{
+        use $crate::__private::AsPlace as _;
+        let slice: &mut [_] = $slice.__as_place();
+        $crate::place!(*$crate::__private::slot(slice, $idx), $($t)+)
+    }};
+}
+
+/// Initialize a struct in-place at `slice[idx]`, and return a mutable
+/// reference, or an error if `idx` is out of bounds
+///
+/// This works exactly like [`place_slice!`], except it returns a
+/// [`PlaceError`] instead of panicking. No initializers are evaluated if `idx`
+/// is out of bounds.
+///
+/// # Errors
+///
+/// If `idx` is out of bounds
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{try_place_slice, PlaceError};
+/// # use std::mem::MaybeUninit;
+///
+/// struct Pair {
+///     a: u32,
+///     b: u32,
+/// }
+///
+/// let mut slots: [MaybeUninit<Pair>; 3] = [const { MaybeUninit::uninit() }; 3];
+///
+/// let x = try_place_slice!(slots, 1, Pair { a: 1, b: 2 });
+/// assert_eq!(x.unwrap().b, 2);
+///
+/// let x = try_place_slice!(slots, 3, Pair { a: 1, b: 2 });
+/// assert!(matches!(x, Err(PlaceError::OutOfBounds { index: 3, len: 3 })));
+/// ```
+///
+/// [`place_slice!`]: crate::place_slice
+#[macro_export]
+macro_rules! try_place_slice {
+    ($slice:expr, $idx:expr, $($t:tt)+) => {{
+        use $crate::__private::AsPlace as _;
+        let slice: &mut [_] = $slice.__as_place();
+        match $crate::__private::try_slot(slice, $idx) {
+            ::core::result::Result::Ok(slot) => {
+                ::core::result::Result::Ok($crate::place!(*slot, $($t)+))
+            }
+            ::core::result::Result::Err(e) => ::core::result::Result::Err(e),
+        }
+    }};
+}
+
+/// Initialize a union in-place at `buf`, and return a mutable reference
+///
+/// This works exactly like [`place!`], except exactly one field must be given,
+/// which is the only one written.
+///
+/// Reading any other field is still your unsafe responsibility, as with any
+/// union.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_union;
+/// # use std::mem::MaybeUninit;
+///
+/// #[repr(C)]
+/// union Bits {
+///     u: u32,
+///     f: f32,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut Bits = place_union!(buf, Bits { f: 1.0 });
+///
+/// // SAFETY: u32 and f32 are the same size, and all bits are valid for both
+/// assert_eq!(unsafe { x.u }, 1.0f32.to_bits());
+/// ```
+///
+/// Giving more than one field is an error
+///
+/// ```rust,compile_fail
+/// # use place::place_union;
+/// # use std::mem::MaybeUninit;
+/// # #[repr(C)]
+/// # union Bits {
+/// #     u: u32,
+/// #     f: f32,
+/// # }
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut Bits = place_union!(buf, Bits { u: 0, f: 1.0 });
+/// ```
+#[macro_export]
+macro_rules! place_union {
+    // Union literals already must have exactly one field, so the usual
+    // completeness check is all that's needed.
+    ($buf:expr, $($t:tt)+) => {
+        $crate::place!($buf, $($t)+)
+    };
+}
+
+/// Initialize a union in-place at `buf` after zeroing it, and return a mutable
+/// reference
+///
+/// This works exactly like [`place_union!`], except every byte of `buf` is
+/// first set to zero, like [`place_zeroed!`]. The bytes of a union not covered
+/// by the field written are zero rather than uninitialized, so the whole union
+/// can be read as bytes, such as to copy it or pass it to C.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_union_zeroed;
+/// # use std::mem::MaybeUninit;
+///
+/// #[repr(C)]
+/// union Value {
+///     byte: u8,
+///     word: u64,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut Value = place_union_zeroed!(buf, Value { byte: 0xff });
+///
+/// // SAFETY: Every byte of x is initialized, and all bits are valid for u64
+/// assert_eq!(unsafe { x.word }.to_le(), 0xff);
+/// ```
+#[macro_export]
+macro_rules! place_union_zeroed {
+    ($buf:expr, $($t:tt)+) => {
+        $crate::place_zeroed!($buf, $($t)+)
+    };
+}
+
+/// Get a `*mut` pointer to a field of the struct being placed by [`place!`]
+///
+/// This may only be used in a field initializer of [`place!`], and refers to
+/// the innermost struct being placed, including nested `place!` fields.
+///
+/// Fields are written in the order they're given, each before the next
+/// initializer is evaluated, so fields given before the current one are
+/// initialized and may be read through the pointer. Later fields are not, and
+/// must not be read, but the pointer may still be stored. The pointer is valid
+/// for as long as the placed value isn't moved.
+///
+/// This can't be used with `..base`, as `base` is only written after all
+/// initializers have been evaluated.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place;
+/// # use std::mem::MaybeUninit;
+///
+/// struct Node {
+///     value: u32,
+///     self_ref: *const u32,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut Node = place!(
+///     buf,
+///     Node {
+///         value: 69420,
+///         self_ref: place::field!(value),
+///     }
+/// );
+/// // SAFETY: x.self_ref points to x.value, which was written first
+/// assert_eq!(unsafe { *x.self_ref }, 69420);
+/// ```
+#[macro_export]
+macro_rules! field {
+    ($field:tt) => {
+        __place_field!($field)
+    };
+}
+
+/// Borrow a field of the struct being placed by [`place!`], which has
+/// already been written
+///
+/// This may only be used in a field initializer of [`place!`], and refers to
+/// the innermost struct being placed, like [`field!`]. Only fields given
+/// before the current one have been written, and the reference can't outlive
+/// the initializer it's used in.
+///
+/// # Panics
+///
+/// If the field hasn't been written yet, or is an unaligned field of a packed
+/// struct
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{field_ref, place};
+/// # use std::mem::MaybeUninit;
+///
+/// struct Packet {
+///     data: [u8; 4],
+///     checksum: u32,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut Packet = place!(
+///     buf,
+///     Packet {
+///         data: [1, 2, 3, 4],
+///         checksum: field_ref!(data).iter().map(|b| *b as u32).sum(),
+///     }
+/// );
+/// assert_eq!(x.checksum, 10);
+/// ```
+///
+/// [`field!`]: crate::field
+/// [`place!`]: crate::place
+#[macro_export]
+macro_rules! field_ref {
+    ($field:tt) => {
+        __place_field_ref!($field)
+    };
+}
+
+/// An uninitialized [`MaybeUninit`], for fields initialized later on purpose
+///
+/// This is [`MaybeUninit::uninit`], for fields of type `MaybeUninit<U>` that
+/// are deliberately left uninitialized, such as a lazily initialized cache.
+/// The field itself is still initialized, as a `MaybeUninit` is always valid,
+/// so this works with [`place!`] and its completeness check, without
+/// constructing a `U`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{place, uninit};
+/// # use std::mem::MaybeUninit;
+///
+/// struct Lazy {
+///     ready: bool,
+///     cache: MaybeUninit<[u64; 512]>,
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut Lazy = place!(
+///     buf,
+///     Lazy {
+///         ready: false,
+///         cache: uninit!(),
+///     }
+/// );
+/// place::place_array!(x.cache, |i| i as u64);
+/// x.ready = true;
+/// ```
+///
+/// [`MaybeUninit`]: core::mem::MaybeUninit
+/// [`MaybeUninit::uninit`]: core::mem::MaybeUninit::uninit
+/// [`place!`]: crate::place
+#[macro_export]
+macro_rules! uninit {
+    () => {
+        ::core::mem::MaybeUninit::uninit()
+    };
+}
+
+/// Places into `$buf`, finally writing the fields with the macro `$emit`
+///
+/// `$emit` is called like `__place_fields`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place {
+    ($emit:ident $buf:expr, enum $($path:tt)+) => {
+        $crate::__place_path!(@enum $emit $buf, [], $($path)+)
+    };
+
+    ($emit:ident $buf:expr, $($path:tt)+) => {
+        $crate::__place_path!(@struct $emit $buf, [], $($path)+)
+    };
+}
+
+/// Collects the path of the type, up until its fields
+///
+/// Path fragments can't be used to name a struct literal, so the path has to
+/// be passed along as tokens.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_path {
+    (
+        @enum $emit:ident $buf:expr,
+        [$($path:tt)+],
+        {
+            $(
+                $f:ident $(: $f_val:expr)?
+            ),*
+            $(,)?
+        }
+    ) => {
+        $crate::__place_variant!(
+            $emit $buf,
+            $($path)+ {
+                $(
+                    $f $(: $f_val)?
+                ),*
+            }
+        )
+    };
+
+    (
+        @enum $emit:ident $buf:expr,
+        [$($path:tt)+],
+        (
+            $(
+                $f_val:expr
+            ),*
+            $(,)?
+        )
+    ) => {
+        $crate::__place_variant!(
+            $emit $buf,
+            $($path)+(
+                $(
+                    $f_val
+                ),*
+            )
+        )
+    };
+
+    (@placer $emit:ident $buf:expr, [$($path:tt)+], { $($fields:tt)* }) => {
+        $crate::$emit!($buf, [$($path)+], $($fields)*)
+    };
+
+    (@dst $emit:ident $buf:expr, [$($path:tt)+], { $($fields:tt)* }) => {
+        $crate::$emit!($buf, [$($path)+], [], [], $($fields)*)
+    };
+
+    (@struct $emit:ident $buf:expr, [$($path:tt)+], { let $($fields:tt)* }) => {
+        $crate::__place_lets!($emit $buf, [$($path)+], [], let $($fields)*)
+    };
+
+    (@struct $emit:ident $buf:expr, [$($path:tt)+], { $($fields:tt)* }) => {
+        $crate::__place_struct!($emit $buf, [$($path)+], [], [], $($fields)*)
+    };
+
+    (
+        @struct $emit:ident $buf:expr,
+        [$($path:tt)+],
+        (
+            $(
+                $f_val:expr
+            ),*
+            $(,)?
+        )
+    ) => {
+        $crate::__place_tuple!(
+            $emit $buf,
+            [$($path)+],
+            [
+                0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+                16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            ],
+            [],
+            $($f_val),*
+        )
+    };
+
+    // Unit structs and variants, which have no fields
+    (@enum $emit:ident $buf:expr, [$($path:tt)*], $last:tt) => {
+        $crate::__place_variant!($emit $buf, $($path)* $last)
+    };
+
+    (@struct $emit:ident $buf:expr, [$($path:tt)*], $last:tt) => {
+        $crate::__place_struct!($emit $buf, [$($path)* $last], [], [],)
+    };
+
+    // The type given separately, as `Path, { .. }`
+    (@$kind:ident $emit:ident $buf:expr, [$($path:tt)+], , { $($fields:tt)* }) => {
+        $crate::__place_path!(@$kind $emit $buf, [$($path)+], { $($fields)* })
+    };
+
+    (@$kind:ident $emit:ident $buf:expr, [$($path:tt)*], $next:tt $($rest:tt)+) => {
+        $crate::__place_path!(@$kind $emit $buf, [$($path)* $next], $($rest)+)
+    };
+}
+
+/// Collects the leading `let` statements of a struct's fields
+///
+/// These are run after `$buf` is evaluated, but before the rest of the
+/// expansion, so that their bindings are in scope for every field initializer.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_lets {
+    // A pattern can't be followed by `:`, so only bindings can have a type
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$($lets:tt)*],
+        let mut $name:ident: $typ:ty = $init:expr; $($rest:tt)*
+    ) => {
+        $crate::__place_lets!(
+            $emit $buf,
+            [$($path)+],
+            [$($lets)* let mut $name: $typ = $init;],
+            $($rest)*
+        )
+    };
+
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$($lets:tt)*],
+        let $name:ident: $typ:ty = $init:expr; $($rest:tt)*
+    ) => {
+        $crate::__place_lets!(
+            $emit $buf,
+            [$($path)+],
+            [$($lets)* let $name: $typ = $init;],
+            $($rest)*
+        )
+    };
+
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$($lets:tt)*],
+        let $pat:pat_param = $init:expr; $($rest:tt)*
+    ) => {
+        $crate::__place_lets!(
+            $emit $buf,
+            [$($path)+],
+            [$($lets)* let $pat = $init;],
+            $($rest)*
+        )
+    };
+
+    // `$buf` is evaluated first, as it is without any `let`s, and borrowed
+    // the same way its emitter would
+    (
+        __place_fields_const $buf:expr,
+        [$($path:tt)+],
+        [$($lets:tt)*],
+        $($fields:tt)*
+    ) => {{
+        let buf: &mut ::core::mem::MaybeUninit<_> = &mut $buf;
+        $($lets)*
+        $crate::__place_struct!(__place_fields_const *buf, [$($path)+], [], [], $($fields)*)
+    }};
+
+    (
+        __place_fields_indexed $buf:expr,
+        [$($path:tt)+],
+        [$($lets:tt)*],
+        $($fields:tt)*
+    ) => {{
+        let (buf, index) = $buf;
+        $($lets)*
+        $crate::__place_struct!(
+            __place_fields_indexed (buf, index),
+            [$($path)+],
+            [],
+            [],
+            $($fields)*
+        )
+    }};
+
+    ($emit:ident $buf:expr, [$($path:tt)+], [$($lets:tt)*], $($fields:tt)*) => {{
+        use $crate::__private::AsPlace as _;
+        let buf = $buf.__as_place();
+        $($lets)*
+        $crate::__place_struct!($emit buf, [$($path)+], [], [], $($fields)*)
+    }};
+}
+
+/// Collects the fields of a struct, and how each will be written
+///
+/// Each field is collected with the attributes it was given, as
+/// `[$(#[$attr])*]`, which are applied everywhere it's used. Doc comments are
+/// dropped, as they'd be unused.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_struct {
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$([$(#[$attr:meta])*] ($f:ident) $kind:tt)*],
+        [],
+    ) => {
+        $crate::$emit!(
+            $buf,
+            ($($path)+ {
+                $(
+                    $(#[$attr])*
+                    $f: loop {}
+                ),*
+            }),
+            [$([$(#[$attr])*] ($f) $kind),*]
+        )
+    };
+
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$([$(#[$attr:meta])*] ($f:ident) $kind:tt)*],
+        [],
+        ..$base:expr
+    ) => {
+        $crate::$emit!(
+            $buf,
+            ($($path)+ {
+                $(
+                    $(#[$attr])*
+                    $f: loop {},
+                )*
+                ..loop {}
+            }),
+            [$([$(#[$attr])*] ($f) $kind),*],
+            ..$base
+        )
+    };
+
+    // Otherwise some fields are marked `@first` or `@last`, and need moving
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$($done:tt)*],
+        [],
+        $(..$base:expr)?
+    ) => {
+        $crate::__place_order!($emit $buf, [$($path)+], [] [] [], [$($done)*], $(..$base)?)
+    };
+
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        #[doc $($doc:tt)*] $($rest:tt)*
+    ) => {
+        $crate::__place_struct!(
+            $emit $buf,
+            [$($path)+],
+            [$($done)*],
+            [$($attrs)*],
+            $($rest)*
+        )
+    };
+
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        #[$attr:meta] $($rest:tt)*
+    ) => {
+        $crate::__place_struct!(
+            $emit $buf,
+            [$($path)+],
+            [$($done)*],
+            [$($attrs)* #[$attr]],
+            $($rest)*
+        )
+    };
+
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        @first $($rest:tt)*
+    ) => {
+        $crate::__place_struct!(
+            $emit $buf,
+            [$($path)+],
+            [$($done)*],
+            [$($attrs)* @first],
+            $($rest)*
+        )
+    };
+
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        @last $($rest:tt)*
+    ) => {
+        $crate::__place_struct!(
+            $emit $buf,
+            [$($path)+],
+            [$($done)*],
+            [$($attrs)* @last],
+            $($rest)*
+        )
+    };
+
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        $f:ident: emplace_fn!($init:expr $(,)?) $(, $($rest:tt)*)?
+    ) => {
+        $crate::__place_struct!(
+            $emit $buf,
+            [$($path)+],
+            [$($done)* [$($attrs)*] ($f) (nested_fn $init)],
+            [],
+            $($($rest)*)?
+        )
+    };
+
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        $f:ident: place!($($inner:tt)+) $(, $($rest:tt)*)?
+    ) => {
+        $crate::__place_struct!(
+            $emit $buf,
+            [$($path)+],
+            [$($done)* [$($attrs)*] ($f) (nested $($inner)+)],
+            [],
+            $($($rest)*)?
+        )
+    };
+
+    // `expr` doesn't match inline `const` blocks before edition 2024, but a
+    // block containing one does
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        $f:ident: const $init:block $(, $($rest:tt)*)?
+    ) => {
+        $crate::__place_struct!(
+            $emit $buf,
+            [$($path)+],
+            [$($done)* [$($attrs)*] ($f) (value { const $init })],
+            [],
+            $($($rest)*)?
+        )
+    };
+
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        $f:ident: $f_val:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::__place_struct!(
+            $emit $buf,
+            [$($path)+],
+            [$($done)* [$($attrs)*] ($f) (value $f_val)],
+            [],
+            $($($rest)*)?
+        )
+    };
+
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        $f:ident $(, $($rest:tt)*)?
+    ) => {
+        $crate::__place_struct!(
+            $emit $buf,
+            [$($path)+],
+            [$($done)* [$($attrs)*] ($f) (value $f)],
+            [],
+            $($($rest)*)?
+        )
+    };
+}
+
+/// Moves the fields marked `@first` to the start, and `@last` to the end,
+/// keeping the order they were given in otherwise
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_order {
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$($first:tt)*] [$($normal:tt)*] [$($last:tt)*],
+        [[$(#[$a:meta])* @first $(#[$b:meta])*] ($f:ident) $kind:tt $($rest:tt)*],
+        $(..$base:expr)?
+    ) => {
+        $crate::__place_order!(
+            $emit $buf,
+            [$($path)+],
+            [$($first)* [$(#[$a])* $(#[$b])*] ($f) $kind] [$($normal)*] [$($last)*],
+            [$($rest)*],
+            $(..$base)?
+        )
+    };
+
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$($first:tt)*] [$($normal:tt)*] [$($last:tt)*],
+        [[$(#[$a:meta])* @last $(#[$b:meta])*] ($f:ident) $kind:tt $($rest:tt)*],
+        $(..$base:expr)?
+    ) => {
+        $crate::__place_order!(
+            $emit $buf,
+            [$($path)+],
+            [$($first)*] [$($normal)*] [$($last)* [$(#[$a])* $(#[$b])*] ($f) $kind],
+            [$($rest)*],
+            $(..$base)?
+        )
+    };
+
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$($first:tt)*] [$($normal:tt)*] [$($last:tt)*],
+        [[$(#[$attr:meta])*] ($f:ident) $kind:tt $($rest:tt)*],
+        $(..$base:expr)?
+    ) => {
+        $crate::__place_order!(
+            $emit $buf,
+            [$($path)+],
+            [$($first)*] [$($normal)* [$(#[$attr])*] ($f) $kind] [$($last)*],
+            [$($rest)*],
+            $(..$base)?
+        )
+    };
+
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$($first:tt)*] [$($normal:tt)*] [$($last:tt)*],
+        [[$($attrs:tt)*] ($f:ident) $kind:tt $($rest:tt)*],
+        $(..$base:expr)?
+    ) => {
+        ::core::compile_error!(::core::concat!(
+            "`",
+            ::core::stringify!($f),
+            "` can only be one of `@first` or `@last`, once"
+        ))
+    };
+
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$($first:tt)*] [$($normal:tt)*] [$($last:tt)*],
+        [],
+        $(..$base:expr)?
+    ) => {
+        $crate::__place_struct!(
+            $emit $buf,
+            [$($path)+],
+            [$($first)* $($normal)* $($last)*],
+            [],
+            $(..$base)?
+        )
+    };
+}
+
+/// Pairs each tuple struct value with its field index
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_tuple {
+    (@never $i:tt) => {
+        loop {}
+    };
+
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$($idx:tt)*],
+        [$($i:tt: $f_val:expr),*],
+    ) => {
+        $crate::$emit!(
+            $buf,
+            ($($path)+(
+                $(
+                    $crate::__place_tuple!(@never $i)
+                ),*
+            )),
+            [$([] ($i) (value $f_val)),*]
+        )
+    };
+
+    (
+        $emit:ident $buf:expr,
+        [$($path:tt)+],
+        [$next:tt $($idx:tt)*],
+        [$($i:tt: $f_val:expr),*],
+        $head:expr $(, $tail:expr)*
+    ) => {
+        $crate::__place_tuple!(
+            $emit $buf,
+            [$($path)+],
+            [$($idx)*],
+            [$($i: $f_val,)* $next: $head],
+            $($tail),*
+        )
+    };
+}
+
+/// Writes the enum variant `$val` to `$buf`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_variant {
+    (__place_fields_const $buf:expr, $val:expr) => {{
+        // Ensures types are correct
+        let buf: &mut ::core::mem::MaybeUninit<_> = &mut $buf;
+        // Struct literal fields are evaluated in order, and dropped by the
+        // compiler if a later one panics.
+        buf.write($val)
+    }};
+
+    (__place_fields_indexed $buf:expr, $val:expr) => {
+        ::core::compile_error!("`indexed` is not supported for enum variants")
+    };
+
+    (__place_fields_raw $buf:expr, $val:expr) => {{
+        use $crate::__private::AsPlace as _;
+        // See the last arm, without creating a reference to the value
+        let buf: &mut ::core::mem::MaybeUninit<_> = $buf.__as_place();
+        let val = $val;
+        let ptr = buf.as_mut_ptr();
+        // SAFETY: ptr is valid for writes
+        #[allow(unused_unsafe)]
+        unsafe {
+            ::core::ptr::write(ptr, val)
+        };
+        ptr
+    }};
+
+    ($emit:ident $buf:expr, $val:expr) => {{
+        use $crate::__private::AsPlace as _;
+        // See above, and `__place_fields`
+        let buf: &mut ::core::mem::MaybeUninit<_> = $buf.__as_place();
+        buf.write($val)
+    }};
+}
+
+/// Writes each field in-place at `$buf`
+///
+/// `$check` must be a parenthesized expression constructing the type from
+/// exactly the fields being written, with `loop {}` for each value.
+///
+/// Each field is given as `[$(#[$attr])*] ($f) (value $f_val)` to write
+/// `$f_val`, or `[$(#[$attr])*] ($f) (nested ..)` to place it in-place with the
+/// tokens given, along with the attributes of the field.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_fields {
+    (
+        $buf:expr,
+        $check:tt,
+        [$($fields:tt)*]
+    ) => {
+        $crate::__place_fields!(@report [] [] $buf, $check, [$($fields)*])
+    };
+
+    // `$index` is a `Cell` to set to the index of the field that failed, and
+    // `raw` returns the pointer instead of a reference
+    (
+        @report [$($index:ident)?] [$($raw:ident)?] $buf:expr,
+        $check:tt,
+        [$([$(#[$attr:meta])*] ($f:tt) $kind:tt),*]
+    ) => {{
+        use $crate::__private::AsPlace as _;
+        // Ensures types are correct, borrowing `$buf` unless it's already a
+        // mutable reference
+        let buf = $buf.__as_place();
+        // This check means Rust will validate that all struct fields were passed in,
+        // meaning that all fields will be initialized below
+        //
+        // This check is the key to making this macro safe.
+        //
+        // It also infers any generic parameters of the type, which couldn't
+        // be omitted if the type was named directly.
+        //
+        // Ignore useless warnings
+        #[allow(unreachable_code, clippy::diverging_sub_expression)]
+        $crate::__private::check(buf, || $check);
+        let ptr = $crate::Place::as_uninit_ptr(buf);
+        // Used by `field!`, which can't name `ptr` itself
+        #[allow(unused_macros)]
+        macro_rules! __place_field {
+            ($field:tt) => {{
+                // SAFETY: ptr points to the value being placed, so the field
+                // is in bounds. Nothing is read.
+                #[allow(unused_unsafe)]
+                let field = unsafe { ::core::ptr::addr_of_mut!((*ptr).$field) };
+                field
+            }};
+        }
+        // The fields in the order they're written. Attributes other than
+        // `cfg` do nothing on these.
+        #[allow(unused_variables, unused_attributes)]
+        let names: &[&str] = &[$($(#[$attr])* ::core::stringify!($f)),*];
+        // Drops the already written fields if a later initializer panics
+        //
+        // The `unsafe` blocks here are redundant when invoked inside another
+        // `unsafe` block, and `mut` is unused for types without fields
+        #[allow(unused_unsafe, unused_mut)]
+        let mut guard = $crate::__private::DropGuard::new(ptr, &[
+            $(
+                $(#[$attr])*
+                |ptr| {
+                    // SAFETY: The guard only calls this for fields that have
+                    // been written
+                    unsafe { $crate::__private::drop_field(::core::ptr::addr_of_mut!((*ptr).$f)) }
+                }
+            ),*
+        ]);
+        $(guard.report($index);)?
+        // Used by `field_ref!`, which checks the field has been written.
+        // Defined after `guard`, so it can refer to it.
+        #[allow(unused_macros)]
+        macro_rules! __place_field_ref {
+            ($field:tt) => {{
+                // SAFETY: See `__place_field`. `field_ref` checks the field
+                // has been written.
+                #[allow(unused_unsafe)]
+                let field = unsafe {
+                    guard.field_ref(
+                        names,
+                        ::core::stringify!($field),
+                        ::core::ptr::addr_of!((*ptr).$field),
+                    )
+                };
+                field
+            }};
+        }
+        $(
+            $(#[$attr])*
+            {
+                $crate::__place_write!(__place_fields ptr, $f, $kind);
+                guard.written();
+            }
+        )*
+        ::core::mem::forget(guard);
+        $crate::__place_fields!(@finish [$($raw)?] buf, ptr)
+    }};
+
+    (@finish [] $buf:ident, $ptr:ident) => {{
+        // SAFETY: All fields have been initialized above
+        // The compiler ensures that all fields were used, all types were correct,
+        // and that size and alignment are correct.
+        //
+        // `buf` isn't used again.
+        #[allow(unused_unsafe)]
+        let init = unsafe { $crate::__private::finish_at(&$buf, $ptr) };
+        init
+    }};
+
+    (@finish [raw] $buf:ident, $ptr:ident) => {
+        $ptr
+    };
+
+    ($buf:expr, $check:tt, [$($fields:tt)*], ..$base:expr) => {
+        $crate::__place_update!($buf, $check, [$($fields)*], ..$base)
+    };
+}
+
+/// [`__place_fields`] for `place_raw!`, returning the pointer to the value
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_fields_raw {
+    ($buf:expr, $check:tt, [$($fields:tt)*]) => {
+        $crate::__place_fields!(@report [] [raw] $buf, $check, [$($fields)*])
+    };
+
+    ($buf:expr, $check:tt, [$($fields:tt)*], ..$base:expr) => {
+        ::core::compile_error!("`..` is not supported by `place_raw!`")
+    };
+}
+
+/// [`__place_fields`] for `try_place!(buf, indexed ..)`, where `$buf` is a
+/// tuple of the buffer and the `Cell` to set to the index of the field that
+/// failed
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_fields_indexed {
+    ($buf:expr, $check:tt, [$($fields:tt)*]) => {{
+        let (buf, index) = $buf;
+        $crate::__place_fields!(@report [index] [] buf, $check, [$($fields)*])
+    }};
+
+    ($buf:expr, $check:tt, [$($fields:tt)*], ..$base:expr) => {
+        ::core::compile_error!("`indexed` can't be combined with `..`")
+    };
+}
+
+/// Writes `$base` at `$buf`, replacing each field given
+///
+/// Like struct update syntax, the fields are evaluated before `$base`. Unlike
+/// it, `$base` is always moved entirely, and the fields it didn't provide are
+/// dropped.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_update {
+    (
+        @eval $ptr:ident,
+        [$($done:tt)*],
+        [[$(#[$attr:meta])*] ($f:tt) ($f_val:expr) $($rest:tt)*],
+        $base:expr
+    ) => {
+        // Every expansion has its own `val`, which is passed along to be
+        // written once all fields and `$base` have been evaluated
+        $(#[$attr])*
+        let val = $f_val;
+        $crate::__place_update!(
+            @eval $ptr,
+            [$($done)* [$(#[$attr])*] ($f) (val)],
+            [$($rest)*],
+            $base
+        );
+    };
+
+    (@eval $ptr:ident, [$([$(#[$attr:meta])*] ($f:tt) ($val:ident))*], [], $base:expr) => {
+        let base = $base;
+        // SAFETY: Only pointers are used, and base initializes every field,
+        // which are each dropped before being replaced.
+        //
+        // If a field panics while being dropped the rest, and base, are leaked
+        #[allow(unused_unsafe)]
+        unsafe {
+            ::core::ptr::write($ptr, base);
+            $(
+                $(#[$attr])*
+                {
+                    $crate::__private::drop_field(::core::ptr::addr_of_mut!((*$ptr).$f));
+                    ::core::ptr::write_unaligned(::core::ptr::addr_of_mut!((*$ptr).$f), $val);
+                }
+            )*
+        };
+    };
+
+    (
+        $buf:expr,
+        $check:tt,
+        [$([$(#[$attr:meta])*] ($f:tt) (value $f_val:expr)),*],
+        ..$base:expr
+    ) => {{
+        use $crate::__private::AsPlace as _;
+        // Ensures types are correct
+        let buf = $buf.__as_place();
+        // See `__place_fields`
+        //
+        // Struct update syntax already covers every field, so this only checks
+        // that the fields exist.
+        #[allow(unreachable_code, clippy::diverging_sub_expression)]
+        $crate::__private::check(buf, || $check);
+        let ptr = $crate::Place::as_uninit_ptr(buf);
+        // If any of these panic, the values evaluated so far are dropped
+        // normally.
+        $crate::__place_update!(@eval ptr, [], [$([$(#[$attr])*] ($f) ($f_val))*], $base);
+        // SAFETY: base initialized the value above, and `buf` isn't used
+        // again
+        #[allow(unused_unsafe)]
+        let init = unsafe { $crate::__private::finish_at(&buf, ptr) };
+        init
+    }};
+
+    ($buf:expr, $check:tt, [$($fields:tt)*], ..$base:expr) => {
+        ::core::compile_error!("nested `place!` fields can't be combined with `..`")
+    };
+}
+
+/// Writes each field in-place at `$buf`, in a way that works in `const`
+///
+/// Like `__place_fields`, but without dropping the written fields on panic,
+/// which can't happen in `const` anyway.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_fields_const {
+    (
+        $buf:expr,
+        $check:tt,
+        [$([$(#[$attr:meta])*] ($f:tt) $kind:tt),*]
+    ) => {{
+        // Ensures types are correct
+        let buf: &mut ::core::mem::MaybeUninit<_> = &mut $buf;
+        // See `__place_fields`
+        #[allow(unreachable_code, clippy::diverging_sub_expression)]
+        $crate::__private::check_const(buf, || $check);
+        let ptr = buf.as_mut_ptr();
+        $(
+            $(#[$attr])*
+            {
+                $crate::__place_write!(__place_fields_const ptr, $f, $kind);
+            }
+        )*
+        // SAFETY: All fields have been initialized above
+        #[allow(unused_unsafe)]
+        let init = unsafe { buf.assume_init_mut() };
+        init
+    }};
+
+    ($buf:expr, $check:tt, [$($fields:tt)*], ..$base:expr) => {
+        ::core::compile_error!("`..` is not supported in `const`")
+    };
+}
+
+/// Writes the field `$f` of `$ptr`, placing nested fields with `$emit`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_write {
+    (__place_fields_const $ptr:ident, $f:tt, (nested $($t:tt)+)) => {
+        $crate::__place_aligned!($ptr, $f);
+        // SAFETY: The field is valid for writes, and nothing else accesses it
+        // until it has been placed
+        #[allow(unused_unsafe)]
+        let field = unsafe {
+            $crate::__private::uninit_mut(::core::ptr::addr_of_mut!((*$ptr).$f))
+        };
+        $crate::__place!(__place_fields_const *field, $($t)+);
+    };
+
+    ($emit:ident $ptr:ident, $f:tt, (value $f_val:expr)) => {
+        // SAFETY: Only a pointer to the field is created, which is in bounds
+        #[allow(unused_unsafe)]
+        let field = unsafe { ::core::ptr::addr_of_mut!((*$ptr).$f) };
+        // Evaluated outside of `unsafe`, so initializers can't perform
+        // unsafe operations without their own `unsafe` block. The type of
+        // the field is known first, so mismatches point at the value.
+        let val = $crate::__private::field_value(field, $f_val);
+        // SAFETY: Only pointers are used, and the compile check in
+        // `__place_fields` ensures all fields were specified
+        #[allow(unused_unsafe)]
+        unsafe {
+            ::core::ptr::write_unaligned(field, val)
+        };
+    };
+
+    ($emit:ident $ptr:ident, $f:tt, (nested $($t:tt)+)) => {
+        $crate::__place_aligned!($ptr, $f);
+        // SAFETY: The field is valid for writes, and nothing else accesses it
+        // until it has been placed
+        #[allow(unused_unsafe)]
+        let mut field = unsafe {
+            $crate::__private::FieldPlace::new(::core::ptr::addr_of_mut!((*$ptr).$f))
+        };
+        $crate::__place!($emit field, $($t)+);
+    };
+
+    ($emit:ident $ptr:ident, $f:tt, (nested_fn $init:expr)) => {
+        $crate::__place_aligned!($ptr, $f);
+        // SAFETY: See `value`
+        #[allow(unused_unsafe)]
+        let field = unsafe { ::core::ptr::addr_of_mut!((*$ptr).$f) };
+        // The type of the field is known first, so closures get the
+        // signature they need
+        let init = $crate::__private::field_init(field, $init);
+        // SAFETY: See `nested`
+        #[allow(unused_unsafe)]
+        let field = unsafe { $crate::__private::uninit_mut(field) };
+        $crate::__private::emplace_fn(field, init);
+    };
+}
+
+/// Rejects fields of packed structs that may be unaligned
+///
+/// Nested fields are placed through a reference, which can't be built to an
+/// unaligned field. Borrowing the field in code that never runs makes this an
+/// error at compile time, for exactly the fields that may be unaligned.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_aligned {
+    ($ptr:ident, $f:tt) => {
+        #[allow(unreachable_code)]
+        if false {
+            // SAFETY: Never run
+            #[allow(unused_unsafe)]
+            let _ = unsafe { &(*$ptr).$f };
+        }
+    };
+}
+
+#[doc(hidden)]
+pub mod __private {
+    use crate::{Place, PlaceError};
+    use core::{
+        cell::{Cell, UnsafeCell},
+        mem::{ManuallyDrop, MaybeUninit},
+        ptr,
+    };
+
+    pub use crate::{
+        array::{
+            check_slots, copy_slice, place_array, place_each, place_grid, place_slice_from_iter,
+            slice_init, slice_ptr, try_check_slots, SliceGuard,
+        },
+        bytes::{bump_uninit, bytes_uninit, bytes_uninit_checked, try_bump_uninit},
+        dst::{check_dst, place_tail, try_place_tail},
+    };
+    #[cfg(feature = "alloc")]
+    pub use crate::{
+        boxed::{try_box_uninit, RawBox},
+        vec::{try_vec_spare, vec_push_at, vec_spare},
+    };
+    #[cfg(feature = "alloc")]
+    pub use alloc::{boxed::Box, rc::Rc, sync::Arc, vec::Vec};
+    #[cfg(feature = "derive")]
+    pub use place_derive::__place_sort_fields;
+
+    /// Borrows a buffer mutably, unless it's already a mutable reference
+    ///
+    /// Called with method syntax, so that auto-ref only borrows buffers that
+    /// aren't references, and references are reborrowed for their whole
+    /// lifetime.
+    pub trait AsPlace {
+        fn __as_place(&mut self) -> &mut Self {
+            self
+        }
+    }
+
+    impl<T: ?Sized> AsPlace for T {}
+
+    /// Ties the type constructed by `check` to that of `buf`
+    ///
+    /// `check` is never called.
+    pub fn check<T, P: Place<T> + ?Sized>(buf: &P, check: fn() -> T) {
+        let _ = (buf, check);
+    }
+
+    /// The value at `ptr`, borrowed for as long as `buf`
+    ///
+    /// This is [`Place::finish`], except the reference is derived from `ptr`
+    /// instead of reborrowing `buf`, which would invalidate `ptr` under
+    /// Stacked Borrows, along with any pointers from `field!` stored in the
+    /// value. `buf` itself is never accessed.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be from [`Place::as_uninit_ptr`] on `buf`, and the value at
+    /// it initialized. `buf` must not be used again while the returned
+    /// reference is.
+    pub unsafe fn finish_at<'a, T, P: Place<T> + ?Sized>(
+        buf: &&'a mut P,
+        ptr: *mut T,
+    ) -> &'a mut T {
+        let _ = buf;
+        // SAFETY: Caller ensures ptr is initialized, and valid for as long as
+        // buf is borrowed
+        unsafe { &mut *ptr }
+    }
+
+    /// A field of the struct being placed, as the buffer of a nested `place!`
+    ///
+    /// Unlike a `&mut MaybeUninit`, the nested struct is written through the
+    /// same pointer as the outer one, so pointers from `field!` to its fields
+    /// aren't invalidated when the outer struct is finished. It derefs to a
+    /// `MaybeUninit` for the buffers that need one, such as enum variants.
+    pub struct FieldPlace<T>(*mut T);
+
+    impl<T> FieldPlace<T> {
+        /// # Safety
+        ///
+        /// `ptr` must be valid for reads and writes, and nothing else may
+        /// access it while this exists
+        pub unsafe fn new(ptr: *mut T) -> Self {
+            Self(ptr)
+        }
+    }
+
+    impl<T> core::ops::Deref for FieldPlace<T> {
+        type Target = MaybeUninit<T>;
+
+        fn deref(&self) -> &MaybeUninit<T> {
+            // SAFETY: See `new`. MaybeUninit<T> has the same layout as T.
+            unsafe { &*self.0.cast() }
+        }
+    }
+
+    impl<T> core::ops::DerefMut for FieldPlace<T> {
+        fn deref_mut(&mut self) -> &mut MaybeUninit<T> {
+            // SAFETY: See `new`
+            unsafe { &mut *self.0.cast() }
+        }
+    }
+
+    // SAFETY: See `new`
+    unsafe impl<T> Place<T> for FieldPlace<T> {
+        fn as_uninit_ptr(&mut self) -> *mut T {
+            self.0
+        }
+
+        unsafe fn finish(&mut self) -> &mut T {
+            // SAFETY: Caller ensures we're initialized
+            unsafe { &mut *self.0 }
+        }
+    }
+
+    /// [`check`] for `const`, which only supports [`MaybeUninit`]
+    pub const fn check_const<T>(buf: &MaybeUninit<T>, check: fn() -> T) {
+        let _ = (buf, check);
+    }
+
+    /// Get a pointer to the value in `cell`
+    pub fn cell_ptr<T>(cell: &UnsafeCell<MaybeUninit<T>>) -> *mut T {
+        cell.get().cast()
+    }
+
+    /// Implementation of `emplace_fn!`
+    #[track_caller]
+    pub fn emplace_fn<T>(
+        buf: &mut MaybeUninit<T>,
+        init: impl FnOnce(&mut MaybeUninit<T>) -> &mut T,
+    ) -> &mut T {
+        let ptr = buf.as_ptr();
+        let init = init(buf);
+        // A reference to anything else wouldn't mean that buf is initialized
+        assert!(
+            ptr::eq(init, ptr),
+            "emplace_fn! initializer returned a reference to something else"
+        );
+        init
+    }
+
+    /// The element of `slice` at `idx`
+    ///
+    /// # Panics
+    ///
+    /// If `idx` is out of bounds
+    #[track_caller]
+    pub fn slot<T>(slice: &mut [T], idx: usize) -> &mut T {
+        &mut slice[idx]
+    }
+
+    /// The element of `slice` at `idx`, if it's in bounds
+    pub fn try_slot<T>(slice: &mut [T], idx: usize) -> Result<&mut T, PlaceError> {
+        let len = slice.len();
+        slice
+            .get_mut(idx)
+            .ok_or(PlaceError::OutOfBounds { index: idx, len })
+    }
+
+    /// Ties the type of `val` to the field at `field`
+    pub const fn field_value<T>(field: *mut T, val: T) -> T {
+        let _ = field;
+        val
+    }
+
+    /// Ties the type of the initializer `init` to that of `field`
+    pub fn field_init<T, F>(field: *mut T, init: F) -> F
+    where
+        F: FnOnce(&mut MaybeUninit<T>) -> &mut T,
+    {
+        let _ = field;
+        init
+    }
+
+    /// Set every byte of `buf` to zero
+    pub fn zero<T>(buf: &mut MaybeUninit<T>) {
+        // SAFETY: buf is valid for writes of T
+        unsafe { buf.as_mut_ptr().write_bytes(0, 1) };
+    }
+
+    /// The `static mut` at `ptr`
+    ///
+    /// # Safety
+    ///
+    /// See `place_static!`
+    pub unsafe fn static_mut<T>(ptr: *mut MaybeUninit<T>) -> &'static mut MaybeUninit<T> {
+        // SAFETY: Caller ensures ptr is a static, which nothing else accesses
+        unsafe { &mut *ptr }
+    }
+
+    /// Treat `ptr` as a [`MaybeUninit`]
+    ///
+    /// # Safety
+    ///
+    /// See `place_ptr!`
+    pub const unsafe fn uninit_mut<'a, T>(ptr: *mut T) -> &'a mut MaybeUninit<T> {
+        // SAFETY: Caller ensures ptr is valid, and MaybeUninit<T> has the
+        // same layout as T
+        unsafe { &mut *ptr.cast() }
+    }
+
+    /// Drop the field at `ptr`, which may be unaligned in a packed struct
+    ///
+    /// # Safety
+    ///
+    /// See [`ptr::drop_in_place`], except `ptr` may be unaligned
+    pub unsafe fn drop_field<T>(ptr: *mut T) {
+        if ptr.is_aligned() {
+            // SAFETY: Caller ensures ptr is valid
+            unsafe { ptr.drop_in_place() }
+        } else {
+            // Unaligned fields can't be dropped in place, like the compiler
+            // does they're moved out to be dropped
+            //
+            // SAFETY: Caller ensures ptr is valid, and it's never used again
+            drop(unsafe { ptr.read_unaligned() })
+        }
+    }
+
+    /// Implementation of `place_with!`
+    ///
+    /// # Safety
+    ///
+    /// See `place_with!`
+    pub unsafe fn place_with<T, P: Place<T> + ?Sized>(
+        buf: &mut P,
+        init: impl FnOnce(*mut T),
+    ) -> &mut T {
+        let ptr = buf.as_uninit_ptr();
+        init(ptr);
+        // SAFETY: Caller ensures init initialized the value, and buf isn't
+        // used again. Pointers init stored from ptr stay valid, unlike with
+        // `Place::finish`.
+        unsafe { finish_at(&buf, ptr) }
+    }
+
+    /// Implementation of `take!`
+    ///
+    /// # Safety
+    ///
+    /// See `take!`
+    pub unsafe fn take<T, P: Place<T> + ?Sized>(buf: &mut P) -> T {
+        let ptr = buf.as_uninit_ptr();
+        // SAFETY: Caller ensures buf is initialized, and won't be used again
+        let value = unsafe { ptr.read() };
+        // Catch the value being used again, whose contents are now owned by
+        // `value`
+        #[cfg(debug_assertions)]
+        // SAFETY: ptr is valid for writes of T, and is now uninitialized
+        unsafe {
+            ptr.write_bytes(TAKEN, 1)
+        };
+        value
+    }
+
+    /// The byte written over buffers by `take!` with debug assertions
+    #[cfg(debug_assertions)]
+    pub const TAKEN: u8 = 0xA5;
+
+    /// Drop the value at `r`, and treat it as a [`MaybeUninit`]
+    ///
+    /// # Safety
+    ///
+    /// `r` must be initialized again before it's used, dropped, or the
+    /// returned reference expires.
+    pub unsafe fn drop_uninit<T>(r: &mut T) -> &mut MaybeUninit<T> {
+        let ptr = ptr::from_mut(r);
+        // SAFETY: r is valid, and the caller ensures it's initialized again.
+        // MaybeUninit<T> has the same layout as T
+        unsafe {
+            ptr.drop_in_place();
+            &mut *ptr.cast()
+        }
+    }
+
+    /// The old value moved out of a reference, which is moved back if
+    /// dropped before [`Replace::finish`]
+    pub struct Replace<'a, T> {
+        buf: &'a mut MaybeUninit<T>,
+        old: ManuallyDrop<T>,
+    }
+
+    impl<'a, T> Replace<'a, T> {
+        /// Move the value out of `r`
+        pub fn new(r: &'a mut T) -> Self {
+            let ptr = ptr::from_mut(r);
+            // SAFETY: r is valid, and initialized. The copy left in r is
+            // overwritten, or the same as old when moved back, so neither is
+            // dropped twice.
+            let old = unsafe { ptr.read() };
+            Self {
+                // SAFETY: MaybeUninit<T> has the same layout as T, and the
+                // value is moved back unless a new one is written
+                buf: unsafe { &mut *ptr.cast() },
+                old: ManuallyDrop::new(old),
+            }
+        }
+
+        /// The buffer to write the new value to
+        pub fn buf(&mut self) -> &mut MaybeUninit<T> {
+            self.buf
+        }
+
+        /// Return the old value, keeping the new one
+        ///
+        /// # Safety
+        ///
+        /// The new value must have been written to [`Replace::buf`]
+        pub unsafe fn finish(self) -> T {
+            let mut this = ManuallyDrop::new(self);
+            // SAFETY: this is never used again
+            unsafe { ManuallyDrop::take(&mut this.old) }
+        }
+    }
+
+    impl<T> Drop for Replace<'_, T> {
+        fn drop(&mut self) {
+            // SAFETY: old is never used again
+            self.buf.write(unsafe { ManuallyDrop::take(&mut self.old) });
+        }
+    }
+
+    /// Aborts by panicking in [`Drop`] while unwinding, unless forgotten
+    pub struct AbortOnUnwind;
+
+    impl Drop for AbortOnUnwind {
+        fn drop(&mut self) {
+            panic!("panicked while reinitializing a value, aborting")
+        }
+    }
+
+    /// Drops the first `init` fields, in reverse order, unless forgotten
+    ///
+    /// Used by the macros to avoid leaking already written fields when a later
+    /// initializer panics.
+    pub struct DropGuard<'a, T: ?Sized> {
+        ptr: *mut T,
+        drops: &'a [fn(*mut T)],
+        init: usize,
+        index: Option<&'a Cell<usize>>,
+    }
+
+    impl<'a, T: ?Sized> DropGuard<'a, T> {
+        /// `drops` must contain one function per field, in the order they
+        /// will be written, dropping that field of `ptr`
+        pub fn new(ptr: *mut T, drops: &'a [fn(*mut T)]) -> Self {
+            Self {
+                ptr,
+                drops,
+                init: 0,
+                index: None,
+            }
+        }
+
+        /// Set `index` to the number of fields written when dropped, which is
+        /// the index of the field that failed
+        pub fn report(&mut self, index: &'a Cell<usize>) {
+            self.index = Some(index);
+        }
+
+        /// Record that the next field has been written
+        pub fn written(&mut self) {
+            self.init += 1;
+        }
+
+        /// Borrow the field `name` at `field`, where `names` are those of each
+        /// field in the order they're written
+        ///
+        /// Borrowing the guard ensures the reference isn't used once another
+        /// field is written.
+        ///
+        /// # Panics
+        ///
+        /// If the field hasn't been written, or is unaligned
+        ///
+        /// # Safety
+        ///
+        /// `field` must point to the field `name` of the value being placed
+        #[track_caller]
+        pub unsafe fn field_ref<F>(&self, names: &[&str], name: &str, field: *const F) -> &F {
+            let written = names[..self.init].contains(&name);
+            assert!(written, "field `{name}` is read before it's written");
+            assert!(field.is_aligned(), "field `{name}` is unaligned");
+            // SAFETY: Caller ensures field is `name`, which has been written,
+            // and is never written again
+            unsafe { &*field }
+        }
+    }
+
+    impl<T: ?Sized> Drop for DropGuard<'_, T> {
+        fn drop(&mut self) {
+            if let Some(index) = self.index {
+                index.set(self.init);
+            }
+            for drop in self.drops[..self.init].iter().rev() {
+                drop(self.ptr);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        cell::{Cell, RefCell},
+        mem::{size_of, MaybeUninit},
+        ptr::{self, addr_of, addr_of_mut, NonNull},
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+    use std::{
+        panic::{self, catch_unwind},
+        sync::Once,
+    };
+
+    #[derive(Debug)]
+    struct MyCoolStruct {
+        b: bool,
+        s: String,
+        v: Vec<String>,
+    }
+
+    #[test]
+    fn miri() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut MyCoolStruct = place!(
+            buf,
+            MyCoolStruct {
+                b: true,
+                s: String::from("works"),
+                v: vec![String::from("works")],
+            }
+        );
+        dbg!(x);
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    #[derive(Debug)]
+    pub(crate) struct DropCount<'a>(pub(crate) &'a AtomicUsize);
+
+    impl Drop for DropCount<'_> {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn explode<T>() -> T {
+        panic!("explode")
+    }
+
+    /// The line `f` panicked at
+    pub(crate) fn panic_line(f: impl FnOnce()) -> u32 {
+        thread_local! {
+            static LINE: Cell<u32> = const { Cell::new(0) };
+        }
+        static HOOK: Once = Once::new();
+        HOOK.call_once(|| {
+            let default = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                if let Some(location) = info.location() {
+                    LINE.with(|line| line.set(location.line()));
+                }
+                default(info);
+            }));
+        });
+        let res = catch_unwind(core::panic::AssertUnwindSafe(f));
+        assert!(res.is_err());
+        LINE.with(Cell::get)
+    }
+
+    #[allow(dead_code)]
+    struct Four<'a> {
+        a: DropCount<'a>,
+        b: DropCount<'a>,
+        c: DropCount<'a>,
+        d: DropCount<'a>,
+    }
+
+    #[test]
+    fn panic_drops_written() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = MaybeUninit::uninit();
+
+        let res = catch_unwind(core::panic::AssertUnwindSafe(|| {
+            let _: &mut Four = place!(
+                buf,
+                Four {
+                    a: DropCount(&drops),
+                    b: DropCount(&drops),
+                    c: explode(),
+                    d: DropCount(&drops),
+                }
+            );
+        }));
+        assert!(res.is_err());
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+    }
+
+    /// The reference has the lifetime of the buffer, not of a reborrow
+    /// inside the macro, so it can be returned
+    #[test]
+    fn returned() {
+        fn init<'a>(buf: &'a mut MaybeUninit<MyCoolStruct>, s: &str) -> &'a mut MyCoolStruct {
+            place!(
+                buf,
+                MyCoolStruct {
+                    b: true,
+                    s: String::from(s),
+                    v: Vec::new(),
+                }
+            )
+        }
+
+        fn init_tuple(buf: &mut MaybeUninit<Id>) -> &mut Id {
+            place!(buf, Id(1))
+        }
+
+        fn init_variant(buf: &mut MaybeUninit<Instr>) -> &mut Instr {
+            place!(buf, enum Instr::Jump(2))
+        }
+
+        let mut buf = MaybeUninit::uninit();
+        let mut other = MaybeUninit::uninit();
+        let x = init(&mut buf, "works");
+        let y = init(&mut other, "too");
+        assert_eq!(x.s, "works");
+        assert_eq!(y.s, "too");
+        // SAFETY: Both have been initialized above
+        unsafe {
+            ptr::drop_in_place(x);
+            ptr::drop_in_place(y);
+        }
+
+        let mut buf = MaybeUninit::uninit();
+        assert_eq!(*init_tuple(&mut buf), Id(1));
+        let mut buf = MaybeUninit::uninit();
+        assert_eq!(*init_variant(&mut buf), Instr::Jump(2));
+    }
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Id(u64);
+
+    #[derive(Debug, PartialEq)]
+    struct Named(String, Vec<String>, u8);
+
+    #[test]
+    fn tuple() {
+        let mut buf = MaybeUninit::uninit();
+        let x: &mut Id = place!(buf, Id(69420));
+        assert_eq!(*x, Id(69420));
+
+        let mut buf = MaybeUninit::uninit();
+        let x: &mut Named = place!(
+            buf,
+            Named(String::from("works"), vec![String::from("works")], 1,)
+        );
+        assert_eq!(x.0, "works");
+        assert_eq!(x.1, ["works"]);
+        assert_eq!(x.2, 1);
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Instr {
+        Push { value: String, count: u8 },
+        Jump(usize),
+    }
+
+    #[test]
+    fn enum_variant() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Instr = place!(
+            buf,
+            enum Instr::Push {
+                value: String::from("works"),
+                count: 2,
+            }
+        );
+        assert_eq!(
+            *x,
+            Instr::Push {
+                value: String::from("works"),
+                count: 2
+            }
+        );
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+
+        let x: &mut Instr = place!(buf, enum Instr::Jump(5));
+        assert_eq!(*x, Instr::Jump(5));
+    }
+
+    mod shapes {
+        #[derive(Debug, PartialEq)]
+        pub struct Pair<A, B> {
+            pub a: A,
+            pub b: B,
+        }
+
+        #[derive(Debug, PartialEq)]
+        pub struct Wrapper<T>(pub T);
+
+        #[derive(Debug, PartialEq)]
+        pub enum Shape<T> {
+            Rect { w: T, h: T },
+            Circle(T),
+            Empty,
+        }
+
+        pub mod nested {
+            use super::Shape;
+
+            pub fn rect(w: u32, h: u32) -> Shape<u32> {
+                let mut buf = core::mem::MaybeUninit::uninit();
+                let x = place!(buf, enum crate::tests::shapes::Shape::Rect { w, h });
+                // SAFETY: buf has been initialized above
+                unsafe { core::ptr::read(x) }
+            }
+        }
+    }
+
+    #[test]
+    fn enum_paths() {
+        use shapes::Shape;
+
+        assert_eq!(shapes::nested::rect(1, 2), Shape::Rect { w: 1, h: 2 });
+
+        let mut buf = MaybeUninit::uninit();
+        let x = place!(buf, enum self::shapes::Shape::<u8>::Circle(3));
+        assert_eq!(*x, Shape::Circle(3));
+
+        let x = place!(buf, enum crate::tests::shapes::Shape::<u8>::Empty);
+        assert_eq!(*x, Shape::Empty);
+    }
+
+    #[test]
+    fn paths() {
+        let mut buf = MaybeUninit::uninit();
+        let x = place!(buf, std::ops::Range { start: 1, end: 5 });
+        assert_eq!(*x, 1..5);
+
+        let mut buf = MaybeUninit::uninit();
+        let x = place!(
+            buf,
+            shapes::Pair::<i32, String> {
+                a: 1,
+                b: String::from("works"),
+            }
+        );
+        assert_eq!(x.a, 1);
+        assert_eq!(x.b, "works");
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+
+        let mut buf = MaybeUninit::uninit();
+        let x = place!(buf, shapes::Wrapper::<u8>(5));
+        assert_eq!(*x, shapes::Wrapper(5));
+    }
+
+    #[test]
+    fn shorthand() {
+        let s = String::from("works");
+        let v = vec![String::from("works")];
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut MyCoolStruct = place!(buf, MyCoolStruct { b: true, s, v });
+        assert!(x.b);
+        assert_eq!(x.s, "works");
+        assert_eq!(x.v, ["works"]);
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    struct Pinned {
+        value: u32,
+        _pin: core::marker::PhantomPinned,
+    }
+
+    #[test]
+    fn pinned() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: core::pin::Pin<&mut Pinned> = place_pin!(
+            // SAFETY: The value is dropped in place below
+            unsafe { &mut buf },
+            Pinned {
+                value: 69420,
+                _pin: core::marker::PhantomPinned,
+            }
+        );
+        assert_eq!(x.value, 69420);
+        let ptr: *const Pinned = &*x;
+        assert_eq!(ptr, buf.as_ptr());
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    #[test]
+    fn raw_ptr() {
+        use std::alloc::{alloc, dealloc, Layout};
+
+        let layout = Layout::new::<MyCoolStruct>();
+        // SAFETY: MyCoolStruct is not zero sized
+        let ptr = unsafe { alloc(layout) }.cast::<MyCoolStruct>();
+        assert!(!ptr.is_null());
+
+        let x: &mut MyCoolStruct = place_ptr!(
+            // SAFETY: ptr was allocated above for MyCoolStruct
+            unsafe { ptr },
+            MyCoolStruct {
+                b: true,
+                s: String::from("works"),
+                v: vec![String::from("works")],
+            }
+        );
+        assert!(x.b);
+        assert_eq!(x.s, "works");
+        assert_eq!(x.v, ["works"]);
+
+        // SAFETY: ptr has been initialized above, and was allocated with layout
+        unsafe {
+            ptr.drop_in_place();
+            dealloc(ptr.cast(), layout);
+        }
+    }
+
+    #[test]
+    fn nonnull() {
+        use std::alloc::{alloc, dealloc, Layout};
+
+        let layout = Layout::new::<MyCoolStruct>();
+        // SAFETY: MyCoolStruct is not zero sized
+        let ptr = NonNull::new(unsafe { alloc(layout) }.cast::<MyCoolStruct>()).unwrap();
+
+        let x: &mut MyCoolStruct = place_nonnull!(
+            // SAFETY: ptr was allocated above for MyCoolStruct
+            unsafe { ptr },
+            MyCoolStruct {
+                b: true,
+                s: String::from("works"),
+                v: Vec::new(),
+            }
+        );
+        assert!(ptr::eq(x, ptr.as_ptr()));
+        assert_eq!(x.s, "works");
+
+        // SAFETY: ptr has been initialized above, and was allocated with layout
+        unsafe {
+            ptr.drop_in_place();
+            dealloc(ptr.as_ptr().cast(), layout);
+        }
+    }
+
+    #[derive(Debug)]
+    struct Inner {
+        s: String,
+        v: Vec<String>,
+    }
+
+    #[derive(Debug)]
+    struct Middle {
+        inner: Inner,
+        tag: u32,
+    }
+
+    #[derive(Debug)]
+    struct Outer {
+        middle: Middle,
+        id: Id,
+        b: bool,
+    }
+
+    #[test]
+    fn nested() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Outer = place!(
+            buf,
+            Outer {
+                middle: place!(Middle {
+                    inner: place!(Inner {
+                        s: String::from("works"),
+                        v: vec![String::from("works")],
+                    }),
+                    tag: 5,
+                }),
+                id: place!(Id(69420)),
+                b: true,
+            }
+        );
+        assert_eq!(x.middle.inner.s, "works");
+        assert_eq!(x.middle.inner.v, ["works"]);
+        assert_eq!(x.middle.tag, 5);
+        assert_eq!(x.id, Id(69420));
+        assert!(x.b);
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    struct Two<'a> {
+        a: DropCount<'a>,
+        b: DropCount<'a>,
+    }
+
+    #[allow(dead_code)]
+    struct NestedFour<'a> {
+        first: Two<'a>,
+        second: Two<'a>,
+    }
+
+    #[test]
+    fn nested_panic() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = MaybeUninit::uninit();
+
+        let res = catch_unwind(core::panic::AssertUnwindSafe(|| {
+            let _: &mut NestedFour = place!(
+                buf,
+                NestedFour {
+                    first: place!(Two {
+                        a: DropCount(&drops),
+                        b: DropCount(&drops),
+                    }),
+                    second: place!(Two {
+                        a: DropCount(&drops),
+                        b: explode(),
+                    }),
+                }
+            );
+        }));
+        assert!(res.is_err());
+        assert_eq!(drops.load(Ordering::Relaxed), 3);
+    }
+
+    #[derive(Debug)]
+    struct Fallible<'a> {
+        a: DropCount<'a>,
+        b: DropCount<'a>,
+        c: DropCount<'a>,
+    }
+
+    fn fallible<'a>(drops: &'a AtomicUsize, ok: bool) -> Result<DropCount<'a>, &'static str> {
+        if ok {
+            Ok(DropCount(drops))
+        } else {
+            Err("failed")
+        }
+    }
+
+    #[test]
+    fn try_ok() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = MaybeUninit::uninit();
+
+        let x: Result<&mut Fallible, &str> = try_place!(
+            buf,
+            Fallible {
+                a: fallible(&drops, true)?,
+                b: fallible(&drops, true)?,
+                c: DropCount(&drops),
+            }
+        );
+        assert!(x.is_ok());
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+        assert_eq!(drops.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn try_err() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = MaybeUninit::uninit();
+
+        let x: Result<&mut Fallible, &str> = try_place!(
+            buf,
+            Fallible {
+                a: fallible(&drops, true)?,
+                b: fallible(&drops, true)?,
+                c: fallible(&drops, false)?,
+            }
+        );
+        assert_eq!(x.unwrap_err(), "failed");
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn try_closure() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = Box::new_uninit();
+
+        // `return` exits `try_place!` rather than the test, and the buffer may
+        // be any `Place`
+        let x: Result<&mut Fallible, &str> = try_place!(
+            buf,
+            Fallible {
+                a: DropCount(&drops),
+                b: {
+                    if drops.load(Ordering::Relaxed) == 0 {
+                        return Err("returned");
+                    }
+                    DropCount(&drops)
+                },
+                c: DropCount(&drops),
+            }
+        );
+        assert_eq!(x.unwrap_err(), "returned");
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+
+        let x: Result<&mut Fallible, (usize, &str)> = try_place!(
+            &mut buf,
+            indexed Fallible {
+                a: DropCount(&drops),
+                b: DropCount(&drops),
+                c: fallible(&drops, true)?,
+            }
+        );
+        assert!(x.is_ok());
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+        assert_eq!(drops.load(Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn try_indexed() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = MaybeUninit::uninit();
+
+        let x: Result<&mut Fallible, (usize, &str)> = try_place!(
+            buf,
+            indexed Fallible {
+                a: fallible(&drops, true)?,
+                b: fallible(&drops, false)?,
+                c: DropCount(&drops),
+            }
+        );
+        assert_eq!(x.unwrap_err(), (1, "failed"));
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+
+        let x: Result<&mut Fallible, (usize, &str)> = try_place!(
+            buf,
+            indexed Fallible {
+                a: fallible(&drops, true)?,
+                b: fallible(&drops, true)?,
+                c: DropCount(&drops),
+            }
+        );
+        assert!(x.is_ok());
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+        assert_eq!(drops.load(Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn slice() {
+        let mut slots: [MaybeUninit<MyCoolStruct>; 3] = [const { MaybeUninit::uninit() }; 3];
+        let slice = &mut slots[..];
+
+        place_slice!(
+            slice,
+            0,
+            MyCoolStruct {
+                b: true,
+                s: String::from("first"),
+                v: Vec::new(),
+            }
+        );
+        place_slice!(
+            slice,
+            2,
+            MyCoolStruct {
+                b: false,
+                s: String::from("third"),
+                v: vec![String::from("works")],
+            }
+        );
+
+        // SAFETY: slots 0 and 2 have been initialized above
+        let (first, third) = unsafe { (slice[0].assume_init_ref(), slice[2].assume_init_ref()) };
+        assert!(first.b);
+        assert_eq!(first.s, "first");
+        assert!(!third.b);
+        assert_eq!(third.s, "third");
+        assert_eq!(third.v, ["works"]);
+
+        // SAFETY: slots 0 and 2 have been initialized above
+        unsafe {
+            slice[0].assume_init_drop();
+            slice[2].assume_init_drop();
+        }
+    }
+
+    #[test]
+    fn slice_location() {
+        let mut slots: [MaybeUninit<Id>; 3] = [const { MaybeUninit::uninit() }; 3];
+
+        let line = panic_line(|| {
+            place_slice!(slots, 3, Id(1));
+        });
+        assert_eq!(line, line!() - 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn slice_out_of_bounds() {
+        let mut slots: [MaybeUninit<Id>; 3] = [const { MaybeUninit::uninit() }; 3];
+        let slice = &mut slots[..];
+        place_slice!(slice, 3, Id(1));
+    }
+
+    #[test]
+    fn try_slice() {
+        let mut slots: [MaybeUninit<Id>; 3] = [const { MaybeUninit::uninit() }; 3];
+
+        let x = try_place_slice!(slots, 2, Id(1));
+        assert_eq!(x.unwrap().0, 1);
+
+        // Nothing is evaluated when out of bounds
+        let x = try_place_slice!(&mut slots[..], 3, Id(explode()));
+        assert_eq!(
+            x.err(),
+            Some(crate::PlaceError::OutOfBounds { index: 3, len: 3 })
+        );
+    }
+
+    #[test]
+    fn slice_ref() {
+        let mut slots: [MaybeUninit<Named>; 2] = [const { MaybeUninit::uninit() }; 2];
+        let (first, rest) = slots.split_at_mut(1);
+
+        let a: &mut Named = place!(
+            first.get_mut(0).unwrap(),
+            Named(String::from("a"), Vec::new(), 1)
+        );
+        let slot = &mut rest[0];
+        let b: &mut Named = place!(slot, Named(String::from("b"), Vec::new(), 2));
+        // Both are still borrowed from `slots`, not the temporaries
+        assert_eq!(a, &Named(String::from("a"), Vec::new(), 1));
+        assert_eq!(b, &Named(String::from("b"), Vec::new(), 2));
+
+        for slot in &mut slots {
+            // SAFETY: Every slot has been initialized above
+            unsafe { slot.assume_init_drop() };
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Table {
+        id: Id,
+        inner: Pair,
+        name: &'static str,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Pair {
+        a: u8,
+        b: u8,
+    }
+
+    const fn table(a: u8) -> Table {
+        const_place!(Table {
+            id: place!(Id(a as u64)),
+            inner: place!(Pair { a, b: a + 1 }),
+            name: "works",
+        })
+    }
+
+    const TABLE: Table = table(5);
+    const _: () = assert!(TABLE.id.0 == 5);
+    const _: () = assert!(TABLE.inner.a == 5 && TABLE.inner.b == 6);
+
+    #[derive(Clone, Copy)]
+    enum Op {
+        Jump(usize),
+    }
+
+    const JUMP: Op = const_place!(enum Op::Jump(5));
+    const _: () = assert!(matches!(JUMP, Op::Jump(5)));
+
+    #[test]
+    fn const_table() {
+        assert_eq!(TABLE.name, "works");
+        assert_eq!(table(1).inner, Pair { a: 1, b: 2 });
+    }
+
+    #[repr(C)]
+    union Bits {
+        u: u32,
+        f: f32,
+    }
+
+    #[test]
+    fn union() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Bits = place_union!(buf, Bits { f: 1.0 });
+        // SAFETY: u32 and f32 are the same size, and all bits are valid for both
+        assert_eq!(unsafe { x.u }, 1.0f32.to_bits());
+
+        let x: &mut Bits = place_union!(buf, Bits { u: 0 });
+        // SAFETY: u32 and f32 are the same size, and all bits are valid for both
+        assert_eq!(unsafe { x.f }, 0.0);
+    }
+
+    #[repr(C)]
+    union Wide {
+        small: u16,
+        big: [u8; 12],
+    }
+
+    #[test]
+    fn union_zeroed() {
+        let mut buf = MaybeUninit::new(Wide { big: [0xaa; 12] });
+
+        let x: &mut Wide = place_union_zeroed!(buf, Wide { small: 0x0101 });
+        // SAFETY: Every byte of x is initialized, and all bits are valid for big
+        assert_eq!(unsafe { x.big }, [1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    static DEFAULT_DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug)]
+    struct Defaulted<'a> {
+        a: u32,
+        count: DropCount<'a>,
+        tail: Vec<String>,
+    }
+
+    impl Default for Defaulted<'_> {
+        fn default() -> Self {
+            Self {
+                a: 5,
+                count: DropCount(&DEFAULT_DROPS),
+                tail: vec![String::from("default")],
+            }
+        }
+    }
+
+    #[test]
+    fn default() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Defaulted = place!(
+            buf,
+            Defaulted {
+                count: DropCount(&drops),
+                ..Default::default()
+            }
+        );
+        assert_eq!(x.a, 5);
+        assert_eq!(x.tail, ["default"]);
+        // The default `count` was replaced, and dropped
+        assert_eq!(DEFAULT_DROPS.load(Ordering::Relaxed), 1);
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+        assert_eq!(DEFAULT_DROPS.load(Ordering::Relaxed), 1);
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn update() {
+        let drops = AtomicUsize::new(0);
+        let base_drops = AtomicUsize::new(0);
+        let base = Defaulted {
+            a: 1,
+            count: DropCount(&base_drops),
+            tail: vec![String::from("base")],
+        };
+        let tail = base.tail.as_ptr();
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Defaulted = place!(
+            buf,
+            Defaulted {
+                a: base.a + 1,
+                count: DropCount(&drops),
+                ..base
+            }
+        );
+        assert_eq!(x.a, 2);
+        // Ownership of the heap data was transferred, not copied
+        assert_eq!(x.tail.as_ptr(), tail);
+        assert_eq!(x.tail, ["base"]);
+        assert_eq!(base_drops.load(Ordering::Relaxed), 1);
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+        assert_eq!(base_drops.load(Ordering::Relaxed), 1);
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+
+    struct Profile<'a> {
+        name: String,
+        count: DropCount<'a>,
+        tags: Vec<String>,
+        bio: String,
+    }
+
+    #[test]
+    fn update_shorthand() {
+        let drops = AtomicUsize::new(0);
+        let base_drops = AtomicUsize::new(0);
+        let base = Profile {
+            name: String::from("base"),
+            count: DropCount(&base_drops),
+            tags: vec![String::from("tag")],
+            bio: String::from("bio"),
+        };
+        let (tags, bio) = (base.tags.as_ptr(), base.bio.as_ptr());
+        let name = String::from("works");
+        let name_ptr = name.as_ptr();
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Profile = place!(
+            buf,
+            Profile {
+                name,
+                count: DropCount(&drops),
+                ..base
+            }
+        );
+        // Each heap buffer was moved once, from where it was given
+        assert_eq!(x.name.as_ptr(), name_ptr);
+        assert_eq!(x.tags.as_ptr(), tags);
+        assert_eq!(x.bio.as_ptr(), bio);
+        assert_eq!(x.name, "works");
+        assert_eq!(x.bio, "bio");
+        // Only the replaced field of base was dropped
+        assert_eq!(base_drops.load(Ordering::Relaxed), 1);
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+        assert_eq!(base_drops.load(Ordering::Relaxed), 1);
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    struct Ordered {
+        first: u8,
+        second: u8,
+        third: u8,
+    }
+
+    #[test]
+    fn evaluation_order() {
+        let log = std::cell::RefCell::new(Vec::new());
+        let record = |name, value| {
+            log.borrow_mut().push(name);
+            value
+        };
+        let mut buf = MaybeUninit::uninit();
+
+        place!(
+            buf,
+            Ordered {
+                third: record("third", 3),
+                first: record("first", 1),
+                second: record("second", 2),
+            }
+        );
+        assert_eq!(*log.borrow(), ["third", "first", "second"]);
+    }
+
+    #[test]
+    fn evaluation_order_lets() {
+        let log = std::cell::RefCell::new(Vec::new());
+        let record = |name, value| {
+            log.borrow_mut().push(name);
+            value
+        };
+        let mut buf = MaybeUninit::uninit();
+
+        // The buffer is evaluated before the leading `let`s, as it is before
+        // the fields without them
+        let x: &mut Ordered = place!(
+            {
+                log.borrow_mut().push("buf");
+                &mut buf
+            },
+            Ordered {
+                let second = record("let", 2);
+                third: record("third", 3),
+                first: record("first", 1),
+                second,
+            }
+        );
+        assert_eq!((x.first, x.second, x.third), (1, 2, 3));
+        assert_eq!(*log.borrow(), ["buf", "let", "third", "first"]);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Locals {
+        ptr: u32,
+        buf: u32,
+        guard: u32,
+        module: u32,
+    }
+
+    #[test]
+    #[allow(clippy::redundant_field_names)]
+    fn hygiene() {
+        // Items named like the ones the macros use aren't shadowed by them
+        mod ptr {
+            pub fn answer() -> u32 {
+                4
+            }
+        }
+        #[allow(dead_code)]
+        mod core {}
+
+        let ptr = 1;
+        let buf = 2;
+        let guard = 3;
+        let mut slot = MaybeUninit::uninit();
+
+        let x: &mut Locals = place!(
+            slot,
+            Locals {
+                ptr: ptr,
+                buf,
+                guard: guard,
+                module: ptr::answer(),
+            }
+        );
+        assert_eq!(
+            x,
+            &Locals {
+                ptr: 1,
+                buf: 2,
+                guard: 3,
+                module: 4,
+            }
+        );
+    }
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Token {
+        r#type: u32,
+        r#fn: String,
+    }
+
+    #[test]
+    fn raw_idents() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Token = place!(
+            buf,
+            Token {
+                r#type: 1,
+                r#fn: String::from("works"),
+            }
+        );
+        assert_eq!(x.r#type, 1);
+        assert_eq!(x.r#fn, "works");
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+
+        let r#type = 2;
+        let x: &mut Token = place!(
+            buf,
+            Token {
+                r#type,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            x,
+            &Token {
+                r#type: 2,
+                r#fn: String::new(),
+            }
+        );
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Gated {
+        a: u32,
+        #[cfg(feature = "alloc")]
+        extra: String,
+        #[cfg(not(feature = "alloc"))]
+        extra: u32,
+        inner: Id,
+    }
+
+    #[test]
+    fn cfg_fields() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Gated = place!(
+            buf,
+            Gated {
+                a: 1,
+                #[cfg(feature = "alloc")]
+                extra: String::from("works"),
+                #[cfg(not(feature = "alloc"))]
+                extra: 2,
+                inner: place!(Id(3)),
+            }
+        );
+        assert_eq!(x.a, 1);
+        #[cfg(feature = "alloc")]
+        assert_eq!(x.extra, "works");
+        #[cfg(not(feature = "alloc"))]
+        assert_eq!(x.extra, 2);
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+
+        let x: &mut Gated = place!(
+            buf,
+            Gated {
+                #[cfg(feature = "alloc")]
+                extra: String::from("works"),
+                #[cfg(not(feature = "alloc"))]
+                extra: 2,
+                ..Default::default()
+            }
+        );
+        assert_eq!(x.a, 0);
+        #[cfg(feature = "alloc")]
+        assert_eq!(x.extra, "works");
+        #[cfg(not(feature = "alloc"))]
+        assert_eq!(x.extra, 2);
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    struct SelfRef {
+        data: String,
+        this: *const String,
+    }
+
+    #[test]
+    fn with() {
+        let mut buf = MaybeUninit::uninit();
+
+        // SAFETY: Every field is written below
+        let x: &mut SelfRef = unsafe {
+            place_with!(buf, SelfRef, |p: *mut SelfRef| {
+                addr_of_mut!((*p).data).write(String::from("works"));
+                addr_of_mut!((*p).this).write(addr_of!((*p).data));
+            })
+        };
+        assert!(ptr::eq(x.this, &x.data));
+        // SAFETY: x.this points to x.data, which is still alive
+        assert_eq!(unsafe { &*x.this }, "works");
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    struct Node {
+        value: String,
+        self_ref: *const String,
+        inner: SelfRef,
+    }
+
+    #[test]
+    fn field_ptr() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Node = place!(
+            buf,
+            Node {
+                value: String::from("works"),
+                self_ref: crate::field!(value),
+                inner: place!(SelfRef {
+                    data: String::from("inner"),
+                    this: crate::field!(data),
+                }),
+            }
+        );
+        assert!(ptr::eq(x.self_ref, &x.value));
+        assert!(ptr::eq(x.inner.this, &x.inner.data));
+        // SAFETY: The pointers point to fields of x, which are still alive
+        unsafe {
+            assert_eq!(*x.self_ref, "works");
+            assert_eq!(*x.inner.this, "inner");
+        }
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    fn make_cool(s: &str) -> MyCoolStruct {
+        place_value!(MyCoolStruct {
+            b: true,
+            s: String::from(s),
+            v: vec![String::from(s)],
+        })
+    }
+
+    #[test]
+    fn value() {
+        let x = make_cool("works");
+        assert!(x.b);
+        assert_eq!(x.s, "works");
+        assert_eq!(x.v, ["works"]);
+    }
+
+    struct Buf<const N: usize> {
+        data: [u8; N],
+        len: usize,
+    }
+
+    #[test]
+    fn const_generics() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x = place!(
+            buf,
+            Buf::<8> {
+                data: [1; 8],
+                len: 2,
+            }
+        );
+        assert_eq!(x.data.len(), 8);
+        assert_eq!(x.len, 2);
+
+        // Inferred from the annotation instead
+        let mut buf = MaybeUninit::uninit();
+        let x: &mut Buf<4> = place!(
+            buf,
+            Buf {
+                data: [1; _],
+                len: 0
+            }
+        );
+        assert_eq!(x.data.len(), 4);
+    }
+
+    struct View<'a> {
+        slice: &'a [u8],
+        cursor: usize,
+    }
+
+    #[test]
+    fn lifetimes() {
+        let data = vec![1, 2, 3];
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut View = place!(
+            buf,
+            View {
+                slice: &data,
+                cursor: 1,
+            }
+        );
+        x.cursor += 1;
+        assert_eq!(x.slice[x.cursor], 3);
+
+        // The view only borrows data, so it's still usable
+        let slice: &[u8] = {
+            // SAFETY: buf has been initialized above
+            let view = unsafe { buf.assume_init() };
+            view.slice
+        };
+        assert!(ptr::eq(slice, data.as_slice()));
+    }
+
+    #[derive(Debug)]
+    struct Reinit<'a> {
+        s: String,
+        count: DropCount<'a>,
+    }
+
+    #[test]
+    fn reinit() {
+        let drops = AtomicUsize::new(0);
+        let mut x = Reinit {
+            s: String::from("old"),
+            count: DropCount(&drops),
+        };
+        let before = ptr::from_ref(&x);
+
+        let r: &mut Reinit = reinit!(
+            &mut x,
+            Reinit {
+                s: String::from("works"),
+                count: DropCount(&drops),
+            }
+        );
+        assert!(ptr::eq(r, before));
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+        assert_eq!(x.s, "works");
+
+        drop(x);
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn replace() {
+        let drops = AtomicUsize::new(0);
+        let mut x = Reinit {
+            s: String::from("old"),
+            count: DropCount(&drops),
+        };
+        let before = ptr::from_ref(&x);
+
+        let old: Reinit = place_replace!(
+            &mut x,
+            Reinit {
+                s: String::from("works"),
+                count: DropCount(&drops),
+            }
+        );
+        assert!(ptr::eq(&x, before));
+        assert_eq!(old.s, "old");
+        assert_eq!(x.s, "works");
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+
+        drop(old);
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+        drop(x);
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn replace_panic() {
+        let drops = AtomicUsize::new(0);
+        let old_drops = AtomicUsize::new(0);
+        let mut x = Reinit {
+            s: String::from("old"),
+            count: DropCount(&old_drops),
+        };
+
+        let res = catch_unwind(core::panic::AssertUnwindSafe(|| {
+            place_replace!(
+                &mut x,
+                Reinit {
+                    count: DropCount(&drops),
+                    s: explode(),
+                }
+            )
+        }));
+        assert!(res.is_err());
+        // The new field was dropped, and the old value put back
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+        assert_eq!(old_drops.load(Ordering::Relaxed), 0);
+        assert_eq!(x.s, "old");
+
+        drop(x);
+        assert_eq!(old_drops.load(Ordering::Relaxed), 1);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Unit;
+
+    #[derive(Debug, PartialEq)]
+    struct Empty {}
+
+    #[derive(Debug, PartialEq)]
+    struct Marked<T> {
+        value: T,
+        _marker: core::marker::PhantomData<T>,
+    }
+
+    #[test]
+    fn zero_sized() {
+        let mut buf = MaybeUninit::uninit();
+        let x: &mut Unit = place!(buf, Unit);
+        assert_eq!(x, &Unit);
+
+        let mut buf = MaybeUninit::uninit();
+        let x: &mut Unit = place!(buf, Unit {});
+        assert_eq!(x, &Unit);
+
+        let mut buf = MaybeUninit::uninit();
+        let x: &mut Empty = place!(buf, Empty {});
+        assert_eq!(x, &Empty {});
+
+        let mut buf = MaybeUninit::uninit();
+        let x: &mut Option<Id> = place!(buf, enum None);
+        assert_eq!(x, &None);
+
+        let mut buf = MaybeUninit::uninit();
+        let x: &mut Marked<String> = place!(
+            buf,
+            Marked {
+                value: String::from("works"),
+                _marker: core::marker::PhantomData,
+            }
+        );
+        assert_eq!(x.value, "works");
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    #[test]
+    fn cell() {
+        let cell = core::cell::UnsafeCell::new(MaybeUninit::uninit());
+
+        let x: *mut MyCoolStruct = place_cell!(
+            // SAFETY: Nothing else has access to cell
+            unsafe { &cell },
+            MyCoolStruct {
+                b: true,
+                s: String::from("works"),
+                v: Vec::new(),
+            }
+        );
+        assert!(ptr::eq(x, cell.get().cast()));
+        // SAFETY: x was initialized above, and nothing else accesses it
+        unsafe {
+            assert_eq!((*x).s, "works");
+            x.drop_in_place();
+        }
+    }
+
+    #[test]
+    fn attributes() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Pair = place!(
+            buf,
+            Pair {
+                /// Doc comments are allowed, and ignored
+                #[allow(clippy::no_effect, unused_parens)]
+                a: (1),
+                // As are comments
+                #[allow(clippy::identity_op)]
+                #[cfg(all())]
+                b: 2 + 0,
+            }
+        );
+        assert_eq!(x, &Pair { a: 1, b: 2 });
+    }
+
+    struct Large {
+        this: *const Large,
+        data: [u64; 512],
+    }
+
+    impl Large {
+        #[inline(never)]
+        fn init(buf: &mut MaybeUninit<Self>) -> &mut Self {
+            let this = buf.as_ptr();
+            place!(
+                buf,
+                Large {
+                    this,
+                    data: [7; 512]
+                }
+            )
+        }
+    }
+
+    struct Holder {
+        id: u32,
+        large: Large,
+    }
+
+    impl Holder {
+        fn init(buf: &mut MaybeUninit<Self>) -> &mut Self {
+            place!(
+                buf,
+                Self {
+                    id: 2,
+                    large: emplace_fn!(Large::init),
+                }
+            )
+        }
+    }
+
+    #[test]
+    fn self_path() {
+        let mut buf = MaybeUninit::uninit();
+        let x = Holder::init(&mut buf);
+        assert_eq!(x.id, 2);
+        assert!(ptr::eq(x.large.this, &x.large));
+    }
+
+    struct Page {
+        id: u32,
+        first: Large,
+        second: Large,
+        tail: [u8; 1024],
+    }
+
+    /// Several kilobytes of nested fields are each initialized at their
+    /// final location, so none is built elsewhere and copied
+    #[test]
+    fn large_in_place() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Page = place!(
+            buf,
+            Page {
+                id: 1,
+                first: emplace_fn!(Large::init),
+                second: emplace_fn!(Large::init),
+                tail: [1; 1024],
+            }
+        );
+        assert!(ptr::eq(x.first.this, &x.first));
+        assert!(ptr::eq(x.second.this, &x.second));
+        assert_eq!(x.second.data, [7; 512]);
+        assert_eq!(x.tail, [1; 1024]);
+        assert_eq!(x.id, 1);
+    }
+
+    #[test]
+    fn emplace() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Large = emplace_fn!(buf, Large::init);
+        assert!(ptr::eq(x.this, x));
+
+        let mut buf = MaybeUninit::uninit();
+        let x: &mut Holder = place!(
+            buf,
+            Holder {
+                id: 1,
+                large: emplace_fn!(Large::init),
+            }
+        );
+        // Initialized where it ends up, not moved there
+        assert!(ptr::eq(x.large.this, &x.large));
+        assert_eq!(x.id, 1);
+        assert_eq!(x.large.data, [7; 512]);
+    }
+
+    #[test]
+    #[should_panic = "returned a reference to something else"]
+    fn emplace_other() {
+        let mut buf = MaybeUninit::uninit();
+        let _: &mut Id = emplace_fn!(buf, |_| Box::leak(Box::new(Id(1))));
+    }
+
+    #[test]
+    fn separate_path() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x = place!(
+            buf,
+            Buf::<4>,
+            {
+                len: 2,
+                data: [1; 4],
+            }
+        );
+        assert_eq!(x.data, [1; 4]);
+
+        let mut buf = MaybeUninit::uninit();
+        let x: &mut Instr = place!(
+            buf,
+            enum Instr::Push,
+            {
+                value: String::from("works"),
+                count: 2,
+            }
+        );
+        assert!(matches!(x, Instr::Push { count: 2, .. }));
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    #[repr(C, packed)]
+    struct Packed {
+        tag: u8,
+        value: u64,
+        name: String,
+    }
+
+    #[test]
+    fn packed() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Packed = place!(
+            buf,
+            Packed {
+                tag: 1,
+                value: u64::MAX,
+                name: String::from("works"),
+            }
+        );
+        let (value, name) = (x.value, addr_of!(x.name));
+        assert_eq!(value, u64::MAX);
+        // SAFETY: name was initialized above, and the copy is never dropped
+        let name = unsafe { core::mem::ManuallyDrop::new(name.read_unaligned()) };
+        assert_eq!(*name, "works");
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    struct Bytes {
+        a: u8,
+        b: [u8; 2],
+    }
+
+    #[repr(C, packed)]
+    struct PackedNested {
+        tag: u64,
+        bytes: Bytes,
+        byte: u8,
+    }
+
+    #[test]
+    fn packed_nested() {
+        let mut buf = MaybeUninit::uninit();
+
+        // Fields that are always aligned can still be nested
+        let x: &mut PackedNested = place!(
+            buf,
+            PackedNested {
+                tag: 1,
+                bytes: place!(Bytes { a: 2, b: [3, 4] }),
+                byte: emplace_fn!(|buf| buf.write(5)),
+            }
+        );
+        let tag = x.tag;
+        assert_eq!(tag, 1);
+        assert_eq!((x.bytes.a, x.bytes.b, x.byte), (2, [3, 4], 5));
+    }
+
+    #[repr(C, packed)]
+    struct PackedCount<'a> {
+        tag: u8,
+        count: DropCount<'a>,
+        value: u64,
+    }
+
+    #[test]
+    fn packed_panic() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = MaybeUninit::uninit();
+
+        let res = catch_unwind(core::panic::AssertUnwindSafe(|| {
+            let _: &mut PackedCount = place!(
+                buf,
+                PackedCount {
+                    tag: 1,
+                    count: DropCount(&drops),
+                    value: explode(),
+                }
+            );
+        }));
+        assert!(res.is_err());
+        // Dropped despite being unaligned
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Exprs {
+        block: String,
+        cond: u8,
+        choice: &'static str,
+        looped: u32,
+    }
+
+    #[test]
+    #[allow(clippy::never_loop)]
+    fn expr_values() {
+        let mut buf = MaybeUninit::uninit();
+        let n = 3;
+
+        let x: &mut Exprs = place!(
+            buf,
+            Exprs {
+                block: {
+                    let t = n * 2;
+                    t.to_string()
+                },
+                cond: if n > 2 { 1 } else { 0 },
+                choice: match n {
+                    0 => "none",
+                    1 | 2 => "some",
+                    _ => "many",
+                },
+                looped: loop {
+                    break n * 10;
+                },
+            }
+        );
+        assert_eq!(
+            *x,
+            Exprs {
+                block: String::from("6"),
+                cond: 1,
+                choice: "many",
+                looped: 30,
+            }
+        );
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Sizes {
+        size: usize,
+        table: [u8; 4],
+    }
+
+    const fn powers() -> [u8; 4] {
+        let mut table = [0; 4];
+        let mut i = 0;
+        while i < 4 {
+            table[i] = 1 << i;
+            i += 1;
+        }
+        table
+    }
+
+    #[test]
+    fn inline_const() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Sizes = place!(
+            buf,
+            Sizes {
+                size: const { size_of::<Exprs>() * 2 },
+                table: const { powers() },
+            }
+        );
+        assert_eq!(x.size, size_of::<Exprs>() * 2);
+        assert_eq!(x.table, [1, 2, 4, 8]);
+
+        const SIZES: Sizes = const_place!(Sizes {
+            size: const { 1 << 12 },
+            table: const { powers() },
+        });
+        assert_eq!(SIZES.size, 4096);
+        assert_eq!(SIZES.table, [1, 2, 4, 8]);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Counted<T> {
+        value: T,
+        count: usize,
+    }
+
+    fn make_counted<T>(buf: &mut MaybeUninit<Counted<T>>, value: T) -> &mut Counted<T> {
+        place!(buf, Counted { value, count: 0 })
+    }
+
+    fn make_pair<T: Clone>(
+        buf: &mut MaybeUninit<shapes::Pair<T, T>>,
+        value: T,
+    ) -> &mut shapes::Pair<T, T> {
+        place!(
+            buf,
+            shapes::Pair {
+                a: value.clone(),
+                b: value,
+            }
+        )
+    }
+
+    #[test]
+    fn generic_fn() {
+        let mut buf = MaybeUninit::uninit();
+        let x = make_counted(&mut buf, 5u8);
+        assert_eq!(*x, Counted { value: 5, count: 0 });
+
+        let mut buf = MaybeUninit::uninit();
+        let x = make_counted(&mut buf, String::from("works"));
+        x.count += 1;
+        assert_eq!(x.value, "works");
+        // SAFETY: buf has been initialized above
+        assert_eq!(unsafe { buf.assume_init_read() }.count, 1);
+
+        let mut buf = MaybeUninit::uninit();
+        assert_eq!(make_pair(&mut buf, 1u32).b, 1);
+
+        let mut buf = MaybeUninit::uninit();
+        assert_eq!(make_pair(&mut buf, vec![1]).a, [1]);
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    struct Checksummed {
+        data: [u8; 8],
+        len: usize,
+        checksum: u32,
+    }
+
+    #[test]
+    fn field_ref() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Checksummed = place!(
+            buf,
+            Checksummed {
+                data: [1, 2, 3, 4, 5, 6, 7, 8],
+                len: field_ref!(data).len(),
+                checksum: field_ref!(data)[..*field_ref!(len)]
+                    .iter()
+                    .map(|b| u32::from(*b))
+                    .sum(),
+            }
+        );
+        assert_eq!(x.len, 8);
+        assert_eq!(x.checksum, 36);
+    }
+
+    #[test]
+    #[should_panic = "field `checksum` is read before it's written"]
+    fn field_ref_unwritten() {
+        let mut buf = MaybeUninit::uninit();
+
+        let _: &mut Checksummed = place!(
+            buf,
+            Checksummed {
+                data: [0; 8],
+                len: *field_ref!(checksum) as usize,
+                checksum: 0,
+            }
+        );
+    }
+
+    #[repr(C)]
+    struct Padded {
+        kind: u8,
+        len: u32,
+        flag: u16,
+        inner: Header,
+    }
+
+    #[repr(C)]
+    struct Header {
+        tag: u8,
+        value: u64,
+    }
+
+    #[test]
+    fn zeroed() {
+        // Non-zero bytes to start with, which the padding must not keep
+        let mut buf = MaybeUninit::<Padded>::uninit();
+        // SAFETY: buf is valid for writes
+        unsafe { buf.as_mut_ptr().write_bytes(0xAA, 1) };
+
+        let x: &mut Padded = place_zeroed!(
+            buf,
+            Padded {
+                kind: 1,
+                len: 2,
+                flag: 3,
+                inner: place!(Header { tag: 4, value: 5 }),
+            }
+        );
+        let ptr = (x as *const Padded).cast::<u8>();
+        // SAFETY: Every byte has been initialized, including padding
+        let bytes = unsafe { std::slice::from_raw_parts(ptr, size_of::<Padded>()) };
+        assert_eq!(bytes[1..4], [0; 3]);
+        assert_eq!(bytes[10..16], [0; 6]);
+        assert_eq!(bytes[17..24], [0; 7]);
+        assert_eq!(bytes[4..8], 2u32.to_ne_bytes());
+    }
+
+    struct Partial<'a> {
+        count: DropCount<'a>,
+        s: String,
+        // Never written
+        #[allow(dead_code)]
+        rest: [u64; 4],
+    }
+
+    #[test]
+    fn partial() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = MaybeUninit::uninit();
+
+        let ptr: *mut Partial = place_partial!(
+            // SAFETY: Only the fields written are accessed below
+            unsafe { &mut buf },
+            Partial {
+                count: DropCount(&drops),
+                s: String::from("works"),
+                ..
+            }
+        );
+        assert!(ptr::eq(ptr, buf.as_ptr()));
+
+        // SAFETY: `s` has been written, and is only read through ptr
+        let s = unsafe { addr_of!((*ptr).s).read() };
+        assert_eq!(s, "works");
+
+        // SAFETY: `count` has been written, and `s` was moved out above
+        unsafe { addr_of_mut!((*ptr).count).drop_in_place() };
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn partial_panic() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = MaybeUninit::<Partial>::uninit();
+
+        let res = catch_unwind(core::panic::AssertUnwindSafe(|| {
+            let _ = place_partial!(
+                // SAFETY: Nothing is accessed
+                unsafe { &mut buf },
+                Partial {
+                    count: DropCount(&drops),
+                    s: explode(),
+                    ..
+                }
+            );
+        }));
+        assert!(res.is_err());
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+
+    struct Cache<K: core::hash::Hash + Eq, V>
+    where
+        V: Clone,
+    {
+        map: std::collections::HashMap<K, V>,
+        last: Option<(K, V)>,
+    }
+
+    #[test]
+    fn bounded_generics() {
+        // K and V are only inferred from the field values
+        let mut buf = MaybeUninit::uninit();
+        let x = place!(
+            buf,
+            Cache {
+                map: std::collections::HashMap::new(),
+                last: Some((1u8, String::from("works"))),
+            }
+        );
+        x.map.insert(2, String::from("too"));
+        assert_eq!(x.last.as_ref().unwrap().1, "works");
+        assert_eq!(x.map[&2], "too");
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+
+        // Or given by the caller
+        let mut buf = MaybeUninit::uninit();
+        let x: &mut Cache<&str, u32> = place!(
+            buf,
+            Cache {
+                map: Default::default(),
+                last: None,
+            }
+        );
+        assert!(x.last.is_none());
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    struct Scaffold {
+        name: String,
+        tags: Vec<String>,
+        table: [u64; 32],
+        #[cfg(any())]
+        missing: u8,
+        id: Option<Id>,
+    }
+
+    #[test]
+    fn default_fields() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Scaffold = place_default!(
+            buf,
+            Scaffold {
+                name,
+                tags,
+                table,
+                #[cfg(any())]
+                missing,
+                id,
+            }
+        );
+        assert!(x.name.is_empty());
+        assert!(x.tags.is_empty());
+        assert_eq!(x.table, [0; 32]);
+        assert!(x.id.is_none());
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    struct Deferred {
+        id: u32,
+        cache: MaybeUninit<String>,
+    }
+
+    #[test]
+    fn uninit_field() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Deferred = place!(
+            buf,
+            Deferred {
+                id: 1,
+                cache: uninit!(),
+            }
+        );
+        assert_eq!(x.id, 1);
+
+        x.cache.write(String::from("later"));
+        // SAFETY: cache has been initialized above
+        assert_eq!(unsafe { x.cache.assume_init_ref() }, "later");
+        // SAFETY: cache has been initialized above
+        unsafe { x.cache.assume_init_drop() };
+
+        const DEFERRED: Deferred = const_place!(Deferred {
+            id: 2,
+            cache: uninit!(),
+        });
+        assert_eq!(DEFERRED.id, 2);
+    }
+
+    struct Container {
+        id: u32,
+        slot: MaybeUninit<Label>,
+    }
+
+    struct Wrapper {
+        inner: Container,
+    }
+
+    struct Label {
+        s: String,
+    }
+
+    #[test]
+    // `(*boxed).slot` is spelled out on purpose
+    #[allow(clippy::explicit_auto_deref)]
+    fn projection() {
+        // A nested field
+        let mut outer = Wrapper {
+            inner: Container {
+                id: 1,
+                slot: MaybeUninit::uninit(),
+            },
+        };
+        let x: &mut Label = place!(
+            outer.inner.slot,
+            Label {
+                s: String::from("nested"),
+            }
+        );
+        assert_eq!(x.s, "nested");
+        assert_eq!(outer.inner.id, 1);
+        // SAFETY: slot has been initialized above
+        unsafe { outer.inner.slot.assume_init_drop() };
+
+        // Through a Box, explicitly and implicitly
+        let mut boxed = Box::new(Container {
+            id: 2,
+            slot: MaybeUninit::uninit(),
+        });
+        let x: &mut Label = place!(
+            (*boxed).slot,
+            Label {
+                s: String::from("deref"),
+            }
+        );
+        assert_eq!(x.s, "deref");
+        // SAFETY: slot has been initialized above
+        unsafe { boxed.slot.assume_init_drop() };
+        let x: &mut Label = place!(
+            boxed.slot,
+            Label {
+                s: String::from("auto"),
+            }
+        );
+        assert_eq!(x.s, "auto");
+        assert_eq!(boxed.id, 2);
+        // SAFETY: slot has been initialized above
+        unsafe { boxed.slot.assume_init_drop() };
+
+        // Through a RefCell borrow
+        let cell = std::cell::RefCell::new(Container {
+            id: 3,
+            slot: MaybeUninit::uninit(),
+        });
+        let mut borrow = cell.borrow_mut();
+        let x: &mut Label = place!(
+            borrow.slot,
+            Label {
+                s: String::from("cell"),
+            }
+        );
+        assert_eq!(x.s, "cell");
+        // SAFETY: slot has been initialized above
+        unsafe { borrow.slot.assume_init_drop() };
+    }
+
+    struct Extent {
+        lo: u32,
+        hi: u32,
+        name: String,
+    }
+
+    #[test]
+    fn leading_lets() {
+        let calls = Cell::new(0);
+        let order = RefCell::new(Vec::new());
+        let expensive = || {
+            calls.set(calls.get() + 1);
+            order.borrow_mut().push("expensive");
+            (1, 9)
+        };
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Extent = place!(
+            buf,
+            Extent {
+                let (lo, hi) = expensive();
+                let name: String = {
+                    order.borrow_mut().push("name");
+                    format!("{lo}..{hi}")
+                };
+                lo: {
+                    order.borrow_mut().push("lo");
+                    lo
+                },
+                hi,
+                name,
+            }
+        );
+        assert_eq!((x.lo, x.hi), (1, 9));
+        assert_eq!(x.name, "1..9");
+        assert_eq!(calls.get(), 1);
+        assert_eq!(*order.borrow(), ["expensive", "name", "lo"]);
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+
+        // With the type given separately
+        let x = place!(buf, Extent, {
+            let lo = expensive().0;
+            lo,
+            hi: lo,
+            name: String::new(),
+        });
+        assert_eq!(x.hi, 1);
+        assert_eq!(calls.get(), 2);
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    struct Logged<'a>(&'static str, &'a RefCell<Vec<&'static str>>);
+
+    impl Drop for Logged<'_> {
+        fn drop(&mut self) {
+            self.1.borrow_mut().push(self.0);
+        }
+    }
+
+    struct LoggedFields<'a> {
+        a: Logged<'a>,
+        b: Logged<'a>,
+        c: Logged<'a>,
+        d: Logged<'a>,
+    }
+
+    #[test]
+    fn reverse_drop_order() {
+        let log = RefCell::new(Vec::new());
+        let mut buf = MaybeUninit::uninit();
+
+        let res = catch_unwind(core::panic::AssertUnwindSafe(|| {
+            // Not in declaration order, to show it's the order written
+            place!(
+                buf,
+                LoggedFields {
+                    c: Logged("c", &log),
+                    a: Logged("a", &log),
+                    d: Logged("d", &log),
+                    b: explode(),
+                }
+            );
+        }));
+        assert!(res.is_err());
+        assert_eq!(*log.borrow(), ["d", "a", "c"]);
+    }
+
+    #[derive(Default)]
+    struct InUnsafe {
+        a: u32,
+        inner: InUnsafeInner,
+        s: String,
+    }
+
+    #[derive(Default)]
+    struct InUnsafeInner {
+        x: u32,
+        y: u32,
+    }
+
+    #[test]
+    #[deny(unused_unsafe)]
+    fn inside_unsafe() {
+        let mut buf = MaybeUninit::uninit();
+        let mut array = MaybeUninit::uninit();
+        let mut old = InUnsafe::default();
+
+        // SAFETY: Each buffer is initialized before being dropped
+        unsafe {
+            place!(
+                buf,
+                InUnsafe {
+                    a: 1,
+                    inner: place!(InUnsafeInner {
+                        x: 2,
+                        y: *field_ref!(x),
+                    }),
+                    s: String::new(),
+                }
+            );
+            buf.assume_init_drop();
+            place!(
+                buf,
+                InUnsafe {
+                    a: 1,
+                    inner: emplace_fn!(|buf| place!(buf, InUnsafeInner { x: 1, y: 2 })),
+                    s: {
+                        let _ = field!(a);
+                        String::new()
+                    },
+                }
+            );
+            buf.assume_init_drop();
+            place!(
+                buf,
+                InUnsafe {
+                    a: 1,
+                    ..Default::default()
+                }
+            );
+            buf.assume_init_drop();
+            place_zeroed!(
+                buf,
+                InUnsafe {
+                    a: 1,
+                    inner: InUnsafeInner { x: 1, y: 2 },
+                    s: String::new(),
+                }
+            );
+            buf.assume_init_drop();
+            let _ = try_place!(
+                buf,
+                indexed InUnsafe {
+                    a: "1".parse()?,
+                    inner: InUnsafeInner { x: 1, y: 2 },
+                    s: String::new(),
+                }
+            )
+            .map_err(|(_, e): (usize, core::num::ParseIntError)| e);
+            buf.assume_init_drop();
+            drop(crate::place_guard!(
+                buf,
+                InUnsafe {
+                    ..Default::default()
+                }
+            ));
+            place_replace!(
+                &mut old,
+                InUnsafe {
+                    a: 2,
+                    inner: InUnsafeInner { x: 1, y: 2 },
+                    s: String::new(),
+                }
+            );
+            crate::place_array!(array, |i| i as u32);
+            let ptr = buf.as_mut_ptr();
+            place_ptr!(
+                unsafe { ptr },
+                InUnsafe {
+                    a: 1,
+                    inner: InUnsafeInner { x: 1, y: 2 },
+                    s: String::new(),
+                }
+            );
+            buf.assume_init_drop();
+        }
+        assert_eq!(old.a, 2);
+        // SAFETY: array has been initialized above
+        assert_eq!(unsafe { array.assume_init() }, [0, 1, 2, 3]);
+    }
+
+    struct Generated {
+        id: u32,
+        name: String,
+        inner: Pair,
+        #[cfg(any())]
+        never: u8,
+    }
+
+    #[test]
+    fn stmts() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Generated = place_stmts!(
+            buf,
+            Generated {
+                id = 1;
+                name = String::from("works");
+                inner = place!(Pair { a: 2, b: 3 });
+                #[cfg(any())]
+                never = 0;
+            }
+        );
+        assert_eq!(x.id, 1);
+        assert_eq!(x.name, "works");
+        assert_eq!(x.inner, Pair { a: 2, b: 3 });
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+
+        // Without the final `;`
+        let x = place_stmts!(
+            buf,
+            Generated {
+                id = 2;
+                name = String::new();
+                inner = emplace_fn!(|buf| place!(buf, Pair { a: 4, b: 5 }))
+            }
+        );
+        assert_eq!(x.inner, Pair { a: 4, b: 5 });
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    struct Annotated {
+        a: u32,
+        b: u32,
+        c: u32,
+        d: u32,
+    }
+
+    struct AnnotatedOuter {
+        b: bool,
+        inner: AnnotatedInner,
+    }
+
+    struct AnnotatedInner {
+        x: u8,
+        y: u32,
+    }
+
+    #[test]
+    fn first_last() {
+        let order = RefCell::new(Vec::new());
+        let log = |name| {
+            order.borrow_mut().push(name);
+            0
+        };
+        let mut buf = MaybeUninit::uninit();
+
+        place!(
+            buf,
+            Annotated {
+                @last a: log("a"),
+                b: log("b"),
+                #[allow(unused_parens)]
+                @first c: (log("c")),
+                @first d: log("d"),
+            }
+        );
+        assert_eq!(*order.borrow(), ["c", "d", "b", "a"]);
+
+        // With `..base`, and nested fields
+        order.borrow_mut().clear();
+        place!(
+            buf,
+            Annotated {
+                @last a: log("a"),
+                @first b: log("b"),
+                ..Annotated {
+                    a: 1,
+                    b: 2,
+                    c: 3,
+                    d: log("base"),
+                }
+            }
+        );
+        assert_eq!(*order.borrow(), ["b", "a", "base"]);
+
+        order.borrow_mut().clear();
+        let mut buf = MaybeUninit::uninit();
+        let x: &mut AnnotatedOuter = place!(
+            buf,
+            AnnotatedOuter {
+                @last b: log("b") == 0,
+                inner: place!(AnnotatedInner {
+                    x: log("x") as u8,
+                    @first y: log("y"),
+                }),
+            }
+        );
+        assert!(x.b);
+        assert_eq!(*order.borrow(), ["y", "x", "b"]);
+    }
+
+    struct Device {
+        id: u32,
+        name: String,
+    }
+
+    #[test]
+    fn static_storage() {
+        static mut STORAGE: MaybeUninit<Device> = MaybeUninit::uninit();
+        static INIT: Once = Once::new();
+
+        fn device() -> &'static Device {
+            INIT.call_once(|| {
+                place_static!(
+                    // SAFETY: STORAGE is a static, and INIT ensures it's only
+                    // initialized once
+                    unsafe { addr_of_mut!(STORAGE) },
+                    Device {
+                        id: 1,
+                        name: String::from("works"),
+                    }
+                );
+            });
+            // SAFETY: INIT has initialized STORAGE above, and it's never
+            // accessed mutably again
+            unsafe { (*addr_of!(STORAGE)).assume_init_ref() }
+        }
+
+        assert_eq!(device().name, "works");
+        assert!(ptr::eq(device(), device()));
+        assert_eq!(device().id, 1);
+    }
+
+    struct Event {
+        id: u32,
+    }
+
+    struct Handlers {
+        handler: fn(&Event) -> u32,
+        callback: Box<dyn Fn() -> u32>,
+        filter: Option<fn(u32) -> bool>,
+    }
+
+    #[test]
+    fn coercion() {
+        let mut buf = MaybeUninit::uninit();
+        let offset = 10;
+
+        let x: &mut Handlers = place!(
+            buf,
+            Handlers {
+                handler: |event| event.id * 2,
+                callback: Box::new(move || offset),
+                filter: Some(|n| n > 1),
+            }
+        );
+        assert_eq!((x.handler)(&Event { id: 2 }), 4);
+        assert_eq!((x.callback)(), 10);
+        assert!(x.filter.unwrap()(2));
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+
+        let x: Result<&mut Handlers, ()> = try_place!(
+            buf,
+            Handlers {
+                handler: Ok(|event: &Event| event.id)?,
+                callback: Box::new(|| 1),
+                filter: None,
+            }
+        );
+        assert_eq!((x.unwrap().handler)(&Event { id: 2 }), 2);
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    struct ConstHandlers {
+        handler: fn(u32) -> u32,
+    }
+
+    const HANDLERS: ConstHandlers = const_place!(ConstHandlers { handler: |n| n + 1 });
+
+    #[test]
+    fn const_coercion() {
+        assert_eq!((HANDLERS.handler)(1), 2);
+    }
+
+    struct Live<'a> {
+        id: u32,
+        count: DropCount<'a>,
+        name: String,
+    }
+
+    #[test]
+    fn replace_one_field() {
+        let old_drops = AtomicUsize::new(0);
+        let new_drops = AtomicUsize::new(0);
+        let mut x = Live {
+            id: 1,
+            count: DropCount(&old_drops),
+            name: String::from("works"),
+        };
+        let name = x.name.as_ptr();
+
+        let old = replace_field!(&mut x, count, DropCount(&new_drops));
+        assert_eq!(old_drops.load(Ordering::Relaxed), 0);
+        drop(old);
+        assert_eq!(old_drops.load(Ordering::Relaxed), 1);
+        // The other fields are untouched
+        assert_eq!(x.id, 1);
+        assert_eq!(x.name.as_ptr(), name);
+
+        // If the new value panics, nothing is replaced or dropped
+        let res = catch_unwind(core::panic::AssertUnwindSafe(|| {
+            let _ = replace_field!(&mut x, count, explode());
+        }));
+        assert!(res.is_err());
+        assert_eq!(new_drops.load(Ordering::Relaxed), 0);
+
+        drop(x);
+        assert_eq!(old_drops.load(Ordering::Relaxed), 1);
+        assert_eq!(new_drops.load(Ordering::Relaxed), 1);
+
+        // Tuple fields
+        let mut pair = (1, String::from("old"));
+        assert_eq!(replace_field!(&mut pair, 1, String::from("new")), "old");
+        assert_eq!(pair.1, "new");
+    }
+
+    #[test]
+    fn raw() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = MaybeUninit::uninit();
+
+        let x: *mut Live = place_raw!(
+            buf,
+            Live {
+                id: 1,
+                count: DropCount(&drops),
+                name: String::from("works"),
+            }
+        );
+        assert!(ptr::eq(x, buf.as_ptr()));
+        // SAFETY: x has been initialized above, and nothing else accesses it
+        assert_eq!(unsafe { &(*x).name }, "works");
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+
+        // Still dropped if a field panics
+        let res = catch_unwind(core::panic::AssertUnwindSafe(|| {
+            place_raw!(
+                buf,
+                Live {
+                    id: 2,
+                    count: DropCount(&drops),
+                    name: explode(),
+                }
+            );
+        }));
+        assert!(res.is_err());
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+
+        let mut buf = MaybeUninit::uninit();
+        let x: *mut Option<u32> = place_raw!(buf, enum Option::Some(1));
+        // SAFETY: x has been initialized above
+        assert_eq!(unsafe { *x }, Some(1));
+    }
+
+    struct Buffer<T = u8> {
+        data: Vec<T>,
+        len: usize,
+    }
+
+    #[test]
+    fn defaulted_params() {
+        // Inferred from the value
+        let mut buf = MaybeUninit::uninit();
+        let x = place!(
+            buf,
+            Buffer {
+                data: vec![1u8, 2],
+                len: 2,
+            }
+        );
+        let _: &mut Buffer = x;
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+
+        // Inferred from the default in the type of the buffer
+        let mut buf: MaybeUninit<Buffer> = MaybeUninit::uninit();
+        let x = place!(
+            buf,
+            Buffer {
+                data: Vec::new(),
+                len: 0,
+            }
+        );
+        x.data.push(u8::MAX);
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+
+        // Given explicitly
+        let mut buf = MaybeUninit::uninit();
+        let x = place!(
+            buf,
+            Buffer::<i32> {
+                data: vec![-1],
+                len: 1,
+            }
+        );
+        assert_eq!(x.data, [-1i32]);
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+    }
+
+    struct Wide2 {
+        n: u64,
+        name: &'static str,
+    }
+
+    struct Narrow {
+        n: u8,
+        name: &'static str,
+    }
+
+    #[cfg(any())]
+    type Chosen = Wide2;
+    #[cfg(not(any()))]
+    type Chosen = Narrow;
+
+    #[test]
+    fn cfg_alias() {
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Narrow = place!(
+            buf,
+            Chosen {
+                n: 1,
+                name: "works",
+            }
+        );
+        assert_eq!(x.name, "works");
+
+        // A type from another macro. Like struct literals, this can't be a
+        // `path` fragment, but may be an `ident` or tokens.
+        macro_rules! place_chosen {
+            ($buf:expr, $typ:ident) => {
+                place!($buf, $typ { n: 2, name: "too" })
+            };
+            ($buf:expr, $($typ:tt)+) => {
+                place!($buf, $($typ)+ { n: 3, name: "tokens" })
+            };
+        }
+        let x: &mut Narrow = place_chosen!(buf, Chosen);
+        assert_eq!(x.n, 2);
+        let mut wide = MaybeUninit::uninit();
+        let x: &mut Wide2 = place_chosen!(wide, Wide2);
+        assert_eq!(x.name, "too");
+        let x: &mut Narrow = place_chosen!(buf, self::Chosen);
+        assert_eq!(x.name, "tokens");
+    }
+
+    struct Owned<'a> {
+        count: DropCount<'a>,
+        name: String,
+        items: Vec<String>,
+    }
+
+    #[test]
+    fn take() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Owned = place!(
+            buf,
+            Owned {
+                count: DropCount(&drops),
+                name: String::from("works"),
+                items: vec![String::from("well")],
+            }
+        );
+        x.items.push(String::from("too"));
+        let name = x.name.as_ptr();
+
+        // SAFETY: buf has been initialized above, and isn't used again
+        let x: Owned = unsafe { take!(buf) };
+        assert_eq!(x.name.as_ptr(), name);
+        assert_eq!(x.items, ["well", "too"]);
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+        #[cfg(debug_assertions)]
+        {
+            // SAFETY: Every byte of buf has been written by take!
+            let bytes: [u8; size_of::<Owned>()] = unsafe { core::mem::transmute_copy(&buf) };
+            assert!(bytes.iter().all(|&b| b == crate::__private::TAKEN));
+        }
+
+        drop(x);
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+
+        // The buffer can be reused, including through a reference
+        let r = &mut buf;
+        place!(
+            r,
+            Owned {
+                count: DropCount(&drops),
+                name: String::from("again"),
+                items: Vec::new(),
+            }
+        );
+        // SAFETY: buf has been initialized above, and isn't used again
+        let x = unsafe { take!(r) };
+        assert_eq!(x.name, "again");
+        drop(x);
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
     }
 }
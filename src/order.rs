@@ -0,0 +1,370 @@
+//! Placing fields in the order they're declared
+
+/// Initialize a struct in-place, writing its fields in the order they're
+/// declared, and return a mutable reference
+///
+/// This works exactly like [`place!`], except fields are evaluated and written
+/// in the order the struct declares them, rather than the order they're given.
+/// Fields marked `@first` or `@last` are still moved before or after the
+/// others.
+///
+/// The struct must derive either [`FieldOrder`] or [`PlaceDefault`], which
+/// record the same order, and be named by a plain path, such as `Node` or
+/// `tree::Node`, from within the crate that defines it. Its leading `let`s
+/// still run first, and enum variants and tuple structs aren't supported.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{place_decl_order, FieldOrder};
+/// # use std::{cell::RefCell, mem::MaybeUninit};
+/// #[derive(FieldOrder)]
+/// struct Connection {
+///     socket: u32,
+///     session: u32,
+/// }
+///
+/// let log = RefCell::new(Vec::new());
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let x: &mut Connection = place_decl_order!(
+///     buf,
+///     Connection {
+///         session: {
+///             log.borrow_mut().push("session");
+///             2
+///         },
+///         socket: {
+///             log.borrow_mut().push("socket");
+///             1
+///         },
+///     }
+/// );
+/// assert_eq!(*log.borrow(), ["socket", "session"]);
+/// assert_eq!((x.socket, x.session), (1, 2));
+/// ```
+///
+/// [`place!`]: crate::place
+/// [`FieldOrder`]: crate::FieldOrder
+/// [`PlaceDefault`]: crate::PlaceDefault
+#[macro_export]
+macro_rules! place_decl_order {
+    ($buf:expr, $($path:ident)::+ { $($fields:tt)* }) => {
+        // Defined by the derive, and calls back with the order
+        $($path)::+!(
+            @fields [$crate::__place_decl_order]
+            $buf,
+            [$($path)::+],
+            [],
+            $($fields)*
+        )
+    };
+}
+
+/// Collects the fields given to `place_decl_order!`, so they can be sorted
+///
+/// Each field is collected as `($f) { .. }`, with the tokens `place!` is given
+/// for it, after any leading `let`s. Fields that `place!` matches by their
+/// tokens, such as nested `place!`s, are kept as tokens.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_decl_order {
+    // See `__place_lets`
+    (
+        [$($order:tt)*] $buf:expr,
+        [$($path:tt)+],
+        [$($lets:tt)*],
+        let mut $name:ident: $typ:ty = $init:expr; $($rest:tt)*
+    ) => {
+        $crate::__place_decl_order!(
+            [$($order)*] $buf,
+            [$($path)+],
+            [$($lets)* let mut $name: $typ = $init;],
+            $($rest)*
+        )
+    };
+
+    (
+        [$($order:tt)*] $buf:expr,
+        [$($path:tt)+],
+        [$($lets:tt)*],
+        let $name:ident: $typ:ty = $init:expr; $($rest:tt)*
+    ) => {
+        $crate::__place_decl_order!(
+            [$($order)*] $buf,
+            [$($path)+],
+            [$($lets)* let $name: $typ = $init;],
+            $($rest)*
+        )
+    };
+
+    (
+        [$($order:tt)*] $buf:expr,
+        [$($path:tt)+],
+        [$($lets:tt)*],
+        let $pat:pat_param = $init:expr; $($rest:tt)*
+    ) => {
+        $crate::__place_decl_order!(
+            [$($order)*] $buf,
+            [$($path)+],
+            [$($lets)* let $pat = $init;],
+            $($rest)*
+        )
+    };
+
+    ([$($order:tt)*] $buf:expr, [$($path:tt)+], [$($lets:tt)*], $($fields:tt)*) => {
+        $crate::__place_decl_order!(
+            @fields [$($order)*] $buf,
+            [$($path)+],
+            [$($lets)*],
+            [],
+            [],
+            $($fields)*
+        )
+    };
+
+    (
+        @fields [$($order:tt)*] $buf:expr,
+        [$($path:tt)+],
+        [$($lets:tt)*],
+        [$($done:tt)*],
+        [],
+        $(..$base:expr)?
+    ) => {
+        $crate::__private::__place_sort_fields!(
+            [$($order)*]
+            [$crate::place]
+            ($buf)
+            [$($path)+]
+            { $($lets)* }
+            [$($done)*]
+            [$(..$base)?]
+        )
+    };
+
+    // Doc comments would be unused
+    (
+        @fields [$($order:tt)*] $buf:expr,
+        [$($path:tt)+],
+        [$($lets:tt)*],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        #[doc $($doc:tt)*] $($rest:tt)*
+    ) => {
+        $crate::__place_decl_order!(
+            @fields [$($order)*] $buf,
+            [$($path)+],
+            [$($lets)*],
+            [$($done)*],
+            [$($attrs)*],
+            $($rest)*
+        )
+    };
+
+    (
+        @fields [$($order:tt)*] $buf:expr,
+        [$($path:tt)+],
+        [$($lets:tt)*],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        #[$attr:meta] $($rest:tt)*
+    ) => {
+        $crate::__place_decl_order!(
+            @fields [$($order)*] $buf,
+            [$($path)+],
+            [$($lets)*],
+            [$($done)*],
+            [$($attrs)* #[$attr]],
+            $($rest)*
+        )
+    };
+
+    (
+        @fields [$($order:tt)*] $buf:expr,
+        [$($path:tt)+],
+        [$($lets:tt)*],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        @$marker:ident $($rest:tt)*
+    ) => {
+        $crate::__place_decl_order!(
+            @fields [$($order)*] $buf,
+            [$($path)+],
+            [$($lets)*],
+            [$($done)*],
+            [$($attrs)* @$marker],
+            $($rest)*
+        )
+    };
+
+    (
+        @fields [$($order:tt)*] $buf:expr,
+        [$($path:tt)+],
+        [$($lets:tt)*],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        $f:ident: emplace_fn!($($init:tt)*) $(, $($rest:tt)*)?
+    ) => {
+        $crate::__place_decl_order!(
+            @fields [$($order)*] $buf,
+            [$($path)+],
+            [$($lets)*],
+            [$($done)* ($f) { $($attrs)* $f: emplace_fn!($($init)*) }],
+            [],
+            $($($rest)*)?
+        )
+    };
+
+    (
+        @fields [$($order:tt)*] $buf:expr,
+        [$($path:tt)+],
+        [$($lets:tt)*],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        $f:ident: place!($($inner:tt)+) $(, $($rest:tt)*)?
+    ) => {
+        $crate::__place_decl_order!(
+            @fields [$($order)*] $buf,
+            [$($path)+],
+            [$($lets)*],
+            [$($done)* ($f) { $($attrs)* $f: place!($($inner)+) }],
+            [],
+            $($($rest)*)?
+        )
+    };
+
+    (
+        @fields [$($order:tt)*] $buf:expr,
+        [$($path:tt)+],
+        [$($lets:tt)*],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        $f:ident: const $init:block $(, $($rest:tt)*)?
+    ) => {
+        $crate::__place_decl_order!(
+            @fields [$($order)*] $buf,
+            [$($path)+],
+            [$($lets)*],
+            [$($done)* ($f) { $($attrs)* $f: const $init }],
+            [],
+            $($($rest)*)?
+        )
+    };
+
+    (
+        @fields [$($order:tt)*] $buf:expr,
+        [$($path:tt)+],
+        [$($lets:tt)*],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        $f:ident: $f_val:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::__place_decl_order!(
+            @fields [$($order)*] $buf,
+            [$($path)+],
+            [$($lets)*],
+            [$($done)* ($f) { $($attrs)* $f: $f_val }],
+            [],
+            $($($rest)*)?
+        )
+    };
+
+    (
+        @fields [$($order:tt)*] $buf:expr,
+        [$($path:tt)+],
+        [$($lets:tt)*],
+        [$($done:tt)*],
+        [$($attrs:tt)*],
+        $f:ident $(, $($rest:tt)*)?
+    ) => {
+        $crate::__place_decl_order!(
+            @fields [$($order)*] $buf,
+            [$($path)+],
+            [$($lets)*],
+            [$($done)* ($f) { $($attrs)* $f }],
+            [],
+            $($($rest)*)?
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{place, FieldOrder};
+    use core::{cell::RefCell, mem::MaybeUninit};
+    use std::vec::Vec;
+
+    #[derive(Debug, PartialEq, FieldOrder)]
+    struct Ordered {
+        first: u8,
+        pub second: u8,
+        pub(crate) third: Option<Result<u8, ()>>,
+    }
+
+    #[test]
+    fn decl_order() {
+        let log = RefCell::new(Vec::new());
+        let record = |name, value: u8| {
+            log.borrow_mut().push(name);
+            value
+        };
+        let mut buf = MaybeUninit::uninit();
+
+        let x: &mut Ordered = place!(
+            buf,
+            Ordered {
+                third: Some(Ok(record("third", 3))),
+                first: record("first", 1),
+                second: record("second", 2),
+            }
+        );
+        assert_eq!(x.third, Some(Ok(3)));
+        assert_eq!(*log.borrow(), ["third", "first", "second"]);
+        log.borrow_mut().clear();
+
+        let x: &mut Ordered = place_decl_order!(
+            buf,
+            Ordered {
+                third: Some(Ok(record("third", 3))),
+                first: record("first", 1),
+                second: record("second", 2),
+            }
+        );
+        assert_eq!(x.third, Some(Ok(3)));
+        assert_eq!(*log.borrow(), ["first", "second", "third"]);
+    }
+
+    #[test]
+    fn decl_order_fields() {
+        let log = RefCell::new(Vec::new());
+        let record = |name, value: u8| {
+            log.borrow_mut().push(name);
+            value
+        };
+        let mut buf = MaybeUninit::uninit();
+
+        // Lets, markers, attributes and shorthand are kept, and the path may
+        // be qualified
+        let x: &mut Ordered = place_decl_order!(
+            &mut buf,
+            self::Ordered {
+                let second = record("let", 2);
+                /// Docs
+                #[cfg(any())]
+                missing: 0,
+                @first third: Some(Ok(record("third", 3))),
+                second,
+                first: const { 1 },
+            }
+        );
+        assert_eq!(
+            *x,
+            Ordered {
+                first: 1,
+                second: 2,
+                third: Some(Ok(3)),
+            }
+        );
+        assert_eq!(*log.borrow(), ["let", "third"]);
+    }
+}
@@ -0,0 +1,272 @@
+//! Initializing a struct one field at a time, checked at runtime
+
+use core::{fmt, mem::MaybeUninit};
+
+/// A struct being initialized in a borrowed [`MaybeUninit`] one field at a
+/// time, such as across several function calls
+///
+/// Created by [`placer!`](crate::placer), and fields are written with
+/// [`place_set!`](crate::place_set). Which fields have been written is
+/// tracked at runtime, and checked by [`Placer::finish`].
+///
+/// Dropping the placer without finishing leaks the fields written so far.
+pub struct Placer<'a, T> {
+    buf: &'a mut MaybeUninit<T>,
+    fields: &'static [&'static str],
+    set: u64,
+}
+
+impl<'a, T> Placer<'a, T> {
+    /// Start initializing `buf`, which has exactly `fields`, with nothing
+    /// written
+    ///
+    /// # Safety
+    ///
+    /// `fields` must be the names of every field of `T`, each once
+    ///
+    /// # Panics
+    ///
+    /// If there are more than 64 fields
+    #[doc(hidden)]
+    pub unsafe fn __new(buf: &'a mut MaybeUninit<T>, fields: &'static [&'static str]) -> Self {
+        assert!(fields.len() <= 64, "Placer supports up to 64 fields");
+        Self {
+            buf,
+            fields,
+            set: 0,
+        }
+    }
+
+    /// Call `write` with a pointer to the value, and whether `field` has
+    /// already been written, then mark it as written
+    ///
+    /// # Safety
+    ///
+    /// `write` must initialize `field`, dropping the old value first if it had
+    /// already been written
+    ///
+    /// # Panics
+    ///
+    /// If `T` has no field named `field`
+    #[doc(hidden)]
+    pub unsafe fn __set_with(&mut self, field: &str, write: impl FnOnce(*mut T, bool)) {
+        let bit = 1 << self.index(field);
+        let set = self.set & bit != 0;
+        // If write panics the field may not be valid, so it's treated as
+        // unwritten and leaked
+        self.set &= !bit;
+        write(self.buf.as_mut_ptr(), set);
+        self.set |= bit;
+    }
+
+    fn index(&self, field: &str) -> usize {
+        self.fields
+            .iter()
+            .position(|f| *f == field)
+            .expect("Placer field doesn't exist")
+    }
+
+    /// Whether the field named `field` has been written
+    ///
+    /// # Panics
+    ///
+    /// If `T` has no field named `field`
+    pub fn is_set(&self, field: &str) -> bool {
+        self.set & (1 << self.index(field)) != 0
+    }
+
+    /// Finish initializing the value, returning a mutable reference to it
+    ///
+    /// # Errors
+    ///
+    /// If any fields haven't been written, the ones that have are leaked.
+    pub fn finish(self) -> Result<&'a mut T, MissingFields> {
+        let all = ((1u128 << self.fields.len()) - 1) as u64;
+        if self.set == all {
+            // SAFETY: Every field has been written
+            Ok(unsafe { self.buf.assume_init_mut() })
+        } else {
+            Err(MissingFields {
+                fields: self.fields,
+                set: self.set,
+            })
+        }
+    }
+}
+
+/// Error returned by [`Placer::finish`] when some fields were never written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingFields {
+    fields: &'static [&'static str],
+    set: u64,
+}
+
+impl MissingFields {
+    /// The names of the fields that weren't written, in the order given to
+    /// [`placer!`](crate::placer)
+    pub fn fields(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.fields
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.set & (1 << i) == 0)
+            .map(|(_, f)| *f)
+    }
+}
+
+impl fmt::Display for MissingFields {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "missing fields")?;
+        for (i, field) in self.fields().enumerate() {
+            let sep = if i == 0 { " " } else { ", " };
+            write!(f, "{sep}`{field}`")?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for MissingFields {}
+
+/// Start initializing a struct at `buf` one field at a time, returning a
+/// [`Placer`]
+///
+/// Every field of the type must be listed, in any order, which is checked at
+/// compile time. `buf` may be anything accepted by [`place!`] that is a
+/// [`MaybeUninit`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{place_set, placer, Placer};
+/// # use std::mem::MaybeUninit;
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// fn set_s(p: &mut Placer<MyCoolStruct>) {
+///     place_set!(p, s, String::from("works"));
+/// }
+///
+/// let mut buf = MaybeUninit::uninit();
+///
+/// let mut p = placer!(buf, MyCoolStruct { b, s });
+/// place_set!(p, b, true);
+/// set_s(&mut p);
+/// let x: &mut MyCoolStruct = p.finish().unwrap();
+///
+/// # // SAFETY: buf has been initialized above
+/// # unsafe { buf.assume_init_drop() };
+/// ```
+///
+/// [`place!`]: crate::place
+#[macro_export]
+macro_rules! placer {
+    ($buf:expr, $($t:tt)+) => {
+        $crate::__place_path!(@placer __placer $buf, [], $($t)+)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __placer {
+    ($buf:expr, [$($path:tt)+], $($f:ident),* $(,)?) => {{
+        use $crate::__private::AsPlace as _;
+        let buf: &mut ::core::mem::MaybeUninit<_> = $buf.__as_place();
+        // See `__place_fields`
+        #[allow(unreachable_code, clippy::diverging_sub_expression)]
+        $crate::__private::check(buf, || $($path)+ { $($f: loop {}),* });
+        // SAFETY: The check ensures these are all the fields, each only once
+        #[allow(unused_unsafe)]
+        let placer = unsafe { $crate::Placer::__new(buf, &[$(::core::stringify!($f)),*]) };
+        placer
+    }};
+}
+
+/// Write the field `$f` of the struct being initialized by the [`Placer`]
+/// `placer`
+///
+/// If the field has already been written, the old value is dropped first.
+///
+/// `placer` may be a `Placer` or a mutable reference to one.
+///
+/// # Examples
+///
+/// See [`placer!`](crate::placer)
+#[macro_export]
+macro_rules! place_set {
+    ($placer:expr, $f:ident, $f_val:expr $(,)?) => {{
+        use $crate::__private::AsPlace as _;
+        let placer: &mut $crate::Placer<_> = $placer.__as_place();
+        // Evaluated outside of `unsafe`, see `__place_write`
+        let val = $f_val;
+        // SAFETY: The field is written below, after dropping any old value
+        #[allow(unused_unsafe)]
+        unsafe {
+            placer.__set_with(::core::stringify!($f), |ptr, set| {
+                let field = ::core::ptr::addr_of_mut!((*ptr).$f);
+                if set {
+                    $crate::__private::drop_field(field);
+                }
+                ::core::ptr::write_unaligned(field, val);
+            })
+        };
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::DropCount;
+    use crate::Placer;
+    use core::{
+        mem::MaybeUninit,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    #[derive(Debug)]
+    struct Parts<'a> {
+        a: u32,
+        s: String,
+        count: DropCount<'a>,
+    }
+
+    fn set_count<'a>(p: &mut Placer<'_, Parts<'a>>, drops: &'a AtomicUsize) {
+        place_set!(p, count, DropCount(drops));
+    }
+
+    #[test]
+    fn placer() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = MaybeUninit::uninit();
+
+        let mut p = placer!(buf, Parts { s, a, count });
+        place_set!(p, a, 1);
+        place_set!(p, s, String::from("works"));
+        set_count(&mut p, &drops);
+        // Replacing a field drops the old value
+        set_count(&mut p, &drops);
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+        assert!(p.is_set("count"));
+
+        let x = p.finish().unwrap();
+        assert_eq!(x.a, 1);
+        assert_eq!(x.s, "works");
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn placer_missing() {
+        let mut buf = MaybeUninit::<Parts>::uninit();
+
+        let mut p = placer!(buf, Parts { a, s, count });
+        place_set!(p, s, String::from("leaks"));
+        assert!(!p.is_set("a"));
+
+        let err = p.finish().unwrap_err();
+        assert_eq!(err.fields().collect::<Vec<_>>(), ["a", "count"]);
+        assert_eq!(err.to_string(), "missing fields `a`, `count`");
+    }
+}
@@ -0,0 +1,173 @@
+//! Placing into a new [`Rc`](alloc::rc::Rc)
+
+/// Initialize a struct in-place in a new [`Rc`], and return it
+///
+/// This works exactly like [`place!`], except the buffer is allocated with
+/// [`Rc::new_uninit`], which requires Rust 1.82. If a field initializer panics
+/// the written fields are dropped, and the allocation is freed.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_rc;
+/// # use std::rc::Rc;
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// let rc: Rc<MyCoolStruct> = place_rc!(MyCoolStruct {
+///     b: true,
+///     s: String::from("works"),
+/// });
+/// ```
+///
+/// [`Rc`]: alloc::rc::Rc
+/// [`Rc::new_uninit`]: alloc::rc::Rc::new_uninit
+/// [`place!`]: crate::place
+#[macro_export]
+macro_rules! place_rc {
+    ($($t:tt)+) => {
+        $crate::__place_shared!(Rc, $($t)+)
+    };
+}
+
+/// [`place_rc!`] and [`place_arc!`], for `$shared` being `Rc` or `Arc`
+///
+/// [`place_rc!`]: crate::place_rc
+/// [`place_arc!`]: crate::place_arc
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __place_shared {
+    ($shared:ident, $($t:tt)+) => {{
+        let mut shared = $crate::__private::$shared::new_uninit();
+        let buf = $crate::__private::$shared::get_mut(&mut shared)
+            .expect(::core::concat!("new ", ::core::stringify!($shared), " is unique"));
+        $crate::place!(*buf, $($t)+);
+        // SAFETY: shared has been initialized above
+        #[allow(unused_unsafe)]
+        let shared = unsafe { shared.assume_init() };
+        shared
+    }};
+}
+
+/// Initialize a struct in-place, given a [`Weak`] pointer to the new [`Rc`]
+/// it will be moved into, and return the `Rc`
+///
+/// This is [`Rc::new_cyclic`] for [`place!`], for values that point to
+/// themselves. The struct is written like `place!`, after the `Weak`, as
+/// `|weak| Path { .. }`, and may store clones of it. It can't be upgraded
+/// until the `Rc` has been returned.
+///
+/// Unlike [`place_rc!`], the struct is placed on the stack and then moved
+/// into the `Rc`, as `Rc::new_cyclic` is the only way to have a `Weak`
+/// that can't be upgraded before the value is initialized.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_rc_cyclic;
+/// # use std::rc::{Rc, Weak};
+///
+/// struct Node {
+///     me: Weak<Node>,
+///     value: u32,
+/// }
+///
+/// let node: Rc<Node> = place_rc_cyclic!(|weak| Node {
+///     me: weak.clone(),
+///     value: 1,
+/// });
+/// assert!(Rc::ptr_eq(&node.me.upgrade().unwrap(), &node));
+/// ```
+///
+/// [`Rc`]: alloc::rc::Rc
+/// [`Weak`]: alloc::rc::Weak
+/// [`Rc::new_cyclic`]: alloc::rc::Rc::new_cyclic
+/// [`place!`]: crate::place
+/// [`place_rc!`]: crate::place_rc
+#[macro_export]
+macro_rules! place_rc_cyclic {
+    (|$weak:ident| $($t:tt)+) => {
+        $crate::__private::Rc::new_cyclic(|$weak| {
+            let mut buf = ::core::mem::MaybeUninit::uninit();
+            $crate::place!(buf, $($t)+);
+            // SAFETY: buf has been initialized above
+            #[allow(unused_unsafe)]
+            let value = unsafe { buf.assume_init() };
+            value
+        })
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{explode, DropCount};
+    use alloc::{
+        rc::{Rc, Weak},
+        string::String,
+    };
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    #[derive(Debug, PartialEq)]
+    struct MyCoolStruct {
+        b: bool,
+        s: String,
+    }
+
+    #[test]
+    fn rc() {
+        let rc: Rc<MyCoolStruct> = place_rc!(MyCoolStruct {
+            b: true,
+            s: String::from("works"),
+        });
+        assert_eq!(Rc::strong_count(&rc), 1);
+        assert_eq!(Rc::weak_count(&rc), 0);
+        assert!(rc.b);
+        assert_eq!(rc.s, "works");
+    }
+
+    struct Node<'a> {
+        count: DropCount<'a>,
+        me: Weak<Node<'a>>,
+        name: String,
+    }
+
+    #[test]
+    fn rc_cyclic() {
+        let drops = AtomicUsize::new(0);
+
+        let node: Rc<Node> = place_rc_cyclic!(|weak| Node {
+            count: DropCount(&drops),
+            me: {
+                assert!(weak.upgrade().is_none());
+                weak.clone()
+            },
+            name: String::from("works"),
+        });
+        assert_eq!(node.name, "works");
+        assert!(Rc::ptr_eq(&node.me.upgrade().unwrap(), &node));
+        assert_eq!(Rc::strong_count(&node), 1);
+        assert_eq!(Rc::weak_count(&node), 1);
+
+        drop(node);
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn rc_cyclic_panic() {
+        let drops = AtomicUsize::new(0);
+
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            let _: Rc<Node> = place_rc_cyclic!(|weak| Node {
+                count: DropCount(&drops),
+                me: weak.clone(),
+                name: explode(),
+            });
+        }));
+        assert!(res.is_err());
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+}
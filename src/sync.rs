@@ -0,0 +1,154 @@
+//! Placing into a new [`Arc`](alloc::sync::Arc)
+
+/// Initialize a struct in-place in a new [`Arc`], and return it
+///
+/// This works exactly like [`place!`], except the buffer is allocated with
+/// [`Arc::new_uninit`], which requires Rust 1.82. If a field initializer
+/// panics the written fields are dropped, and the allocation is freed.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_arc;
+/// # use std::sync::Arc;
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// let arc: Arc<MyCoolStruct> = place_arc!(MyCoolStruct {
+///     b: true,
+///     s: String::from("works"),
+/// });
+/// ```
+///
+/// [`Arc`]: alloc::sync::Arc
+/// [`Arc::new_uninit`]: alloc::sync::Arc::new_uninit
+/// [`place!`]: crate::place
+#[macro_export]
+macro_rules! place_arc {
+    ($($t:tt)+) => {
+        $crate::__place_shared!(Arc, $($t)+)
+    };
+}
+
+/// Initialize a struct in-place, given a [`Weak`] pointer to the new [`Arc`]
+/// it will be moved into, and return the `Arc`
+///
+/// This is [`Arc::new_cyclic`] for [`place!`], for values that point to
+/// themselves. The struct is written like `place!`, after the `Weak`, as
+/// `|weak| Path { .. }`, and may store clones of it. It can't be upgraded
+/// until the `Arc` has been returned.
+///
+/// Unlike [`place_arc!`], the struct is placed on the stack and then moved
+/// into the `Arc`, as `Arc::new_cyclic` is the only way to have a `Weak`
+/// that can't be upgraded before the value is initialized.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_arc_cyclic;
+/// # use std::sync::{Arc, Weak};
+///
+/// struct Node {
+///     me: Weak<Node>,
+///     value: u32,
+/// }
+///
+/// let node: Arc<Node> = place_arc_cyclic!(|weak| Node {
+///     me: weak.clone(),
+///     value: 1,
+/// });
+/// assert!(Arc::ptr_eq(&node.me.upgrade().unwrap(), &node));
+/// ```
+///
+/// [`Arc`]: alloc::sync::Arc
+/// [`Weak`]: alloc::sync::Weak
+/// [`Arc::new_cyclic`]: alloc::sync::Arc::new_cyclic
+/// [`place!`]: crate::place
+/// [`place_arc!`]: crate::place_arc
+#[macro_export]
+macro_rules! place_arc_cyclic {
+    (|$weak:ident| $($t:tt)+) => {
+        $crate::__private::Arc::new_cyclic(|$weak| {
+            let mut buf = ::core::mem::MaybeUninit::uninit();
+            $crate::place!(buf, $($t)+);
+            // SAFETY: buf has been initialized above
+            #[allow(unused_unsafe)]
+            let value = unsafe { buf.assume_init() };
+            value
+        })
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{explode, DropCount};
+    use alloc::{
+        string::String,
+        sync::{Arc, Weak},
+    };
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    #[derive(Debug, PartialEq)]
+    struct MyCoolStruct {
+        b: bool,
+        s: String,
+    }
+
+    #[test]
+    fn arc() {
+        let arc: Arc<MyCoolStruct> = place_arc!(MyCoolStruct {
+            b: true,
+            s: String::from("works"),
+        });
+        assert_eq!(Arc::strong_count(&arc), 1);
+        assert_eq!(Arc::weak_count(&arc), 0);
+        assert!(arc.b);
+        assert_eq!(arc.s, "works");
+    }
+
+    struct Node<'a> {
+        count: DropCount<'a>,
+        me: Weak<Node<'a>>,
+        name: String,
+    }
+
+    #[test]
+    fn arc_cyclic() {
+        let drops = AtomicUsize::new(0);
+
+        let node: Arc<Node> = place_arc_cyclic!(|weak| Node {
+            count: DropCount(&drops),
+            me: {
+                assert!(weak.upgrade().is_none());
+                weak.clone()
+            },
+            name: String::from("works"),
+        });
+        assert_eq!(node.name, "works");
+        assert!(Arc::ptr_eq(&node.me.upgrade().unwrap(), &node));
+        assert_eq!(Arc::strong_count(&node), 1);
+        assert_eq!(Arc::weak_count(&node), 1);
+
+        drop(node);
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn arc_cyclic_panic() {
+        let drops = AtomicUsize::new(0);
+
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            let _: Arc<Node> = place_arc_cyclic!(|weak| Node {
+                count: DropCount(&drops),
+                me: weak.clone(),
+                name: explode(),
+            });
+        }));
+        assert!(res.is_err());
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,209 @@
+//! A method based wrapper around `MaybeUninit`
+
+use crate::Place;
+use core::{
+    mem::MaybeUninit,
+    ops::{Deref, DerefMut},
+};
+
+/// A [`MaybeUninit`], with methods for placing into it
+///
+/// This has the same layout as `MaybeUninit<T>`, and derefs to it, so it can
+/// be used anywhere a `MaybeUninit` can, including as the buffer of
+/// [`place!`](crate::place) and the other macros.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{place, Uninit};
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// let mut buf = Uninit::new();
+///
+/// let x: &mut MyCoolStruct = buf.place(|buf| {
+///     place!(
+///         buf,
+///         MyCoolStruct {
+///             b: true,
+///             s: String::from("works"),
+///         }
+///     )
+/// });
+/// assert_eq!(x.s, "works");
+///
+/// // SAFETY: buf has been initialized above
+/// unsafe { buf.assume_init_drop() };
+///
+/// // Or directly
+/// let x: &mut MyCoolStruct = place!(
+///     buf,
+///     MyCoolStruct {
+///         b: false,
+///         s: String::from("works again"),
+///     }
+/// );
+/// assert_eq!(x.s, "works again");
+///
+/// // SAFETY: buf has been initialized above
+/// unsafe { buf.assume_init_drop() };
+/// ```
+#[repr(transparent)]
+pub struct Uninit<T>(MaybeUninit<T>);
+
+impl<T> Uninit<T> {
+    /// Create a new uninitialized buffer
+    pub const fn new() -> Self {
+        Self(MaybeUninit::uninit())
+    }
+
+    /// Initialize the buffer by calling `init` with it, and return a mutable
+    /// reference
+    ///
+    /// This works exactly like [`emplace_fn!`](crate::emplace_fn), so `init`
+    /// is usually a [`place!`](crate::place).
+    ///
+    /// It is your responsibility to drop the value if needed, such as with
+    /// [`Uninit::assume_init_drop`].
+    ///
+    /// # Panics
+    ///
+    /// If `init` returns a reference to anything other than the buffer
+    #[track_caller]
+    pub fn place(&mut self, init: impl FnOnce(&mut MaybeUninit<T>) -> &mut T) -> &mut T {
+        crate::__private::emplace_fn(&mut self.0, init)
+    }
+
+    /// Pointer to the, possibly uninitialized, value
+    pub const fn as_ptr(&self) -> *const T {
+        self.0.as_ptr()
+    }
+
+    /// Mutable pointer to the, possibly uninitialized, value
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.0.as_mut_ptr()
+    }
+
+    /// Drop the value in the buffer, leaving it uninitialized
+    ///
+    /// # Safety
+    ///
+    /// The buffer must be initialized
+    pub unsafe fn assume_init_drop(&mut self) {
+        // SAFETY: Caller ensures we're initialized
+        unsafe { self.0.assume_init_drop() }
+    }
+}
+
+impl<T> Default for Uninit<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Deref for Uninit<T> {
+    type Target = MaybeUninit<T>;
+
+    fn deref(&self) -> &MaybeUninit<T> {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Uninit<T> {
+    fn deref_mut(&mut self) -> &mut MaybeUninit<T> {
+        &mut self.0
+    }
+}
+
+// SAFETY: Forwards to the MaybeUninit implementation
+unsafe impl<T> Place<T> for Uninit<T> {
+    fn as_uninit_ptr(&mut self) -> *mut T {
+        self.0.as_mut_ptr()
+    }
+
+    unsafe fn finish(&mut self) -> &mut T {
+        // SAFETY: Caller ensures we're initialized
+        unsafe { self.0.assume_init_mut() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Uninit;
+    use crate::tests::{explode, DropCount};
+    use crate::{place, place_zeroed};
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    struct Slot<'a> {
+        count: DropCount<'a>,
+        n: u8,
+    }
+
+    #[test]
+    fn uninit() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = Uninit::new();
+
+        let x: &mut Slot = buf.place(|buf| {
+            place!(
+                buf,
+                Slot {
+                    count: DropCount(&drops),
+                    n: 1,
+                }
+            )
+        });
+        assert_eq!(x.n, 1);
+
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+
+        // Directly, and with the macros needing a MaybeUninit
+        let ptr = buf.as_ptr();
+        let x: &mut Slot = place!(
+            buf,
+            Slot {
+                count: DropCount(&drops),
+                n: 2,
+            }
+        );
+        assert!(core::ptr::eq(x, ptr));
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+        place_zeroed!(
+            buf,
+            Slot {
+                count: DropCount(&drops),
+                n: 3,
+            }
+        );
+        // SAFETY: buf has been initialized above
+        unsafe { buf.assume_init_drop() };
+        assert_eq!(drops.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn uninit_panic() {
+        let drops = AtomicUsize::new(0);
+        let mut buf = Uninit::new();
+
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            buf.place(|buf| {
+                place!(
+                    buf,
+                    Slot {
+                        count: DropCount(&drops),
+                        n: explode(),
+                    }
+                )
+            });
+        }));
+        assert!(res.is_err());
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,293 @@
+//! Placing into the spare capacity of a [`Vec`](alloc::vec::Vec)
+
+use crate::PlaceError;
+use alloc::vec::Vec;
+use core::{alloc::Layout, mem::MaybeUninit};
+
+/// Initialize a struct in-place at the end of a [`Vec`], and return a mutable
+/// reference
+///
+/// This works exactly like [`place!`], except the buffer is the first slot of
+/// spare capacity of `vec`, after reserving it, and the length is increased
+/// once it's initialized. This avoids building the value elsewhere and moving
+/// it into `vec`, like [`Vec::push`] does.
+///
+/// `vec` is a `Vec`, or a mutable reference to one. If a field initializer
+/// panics, the written fields are dropped, and the length is unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::place_vec_push;
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// let mut vec = Vec::new();
+///
+/// let x: &mut MyCoolStruct = place_vec_push!(
+///     vec,
+///     MyCoolStruct {
+///         b: true,
+///         s: String::from("works"),
+///     }
+/// );
+/// x.s.push_str(" well");
+/// assert_eq!(vec[0].s, "works well");
+/// ```
+///
+/// [`Vec`]: alloc::vec::Vec
+/// [`Vec::push`]: alloc::vec::Vec::push
+/// [`place!`]: crate::place
+#[macro_export]
+macro_rules! place_vec_push {
+    ($vec:expr, $($t:tt)+) => {{
+        use $crate::__private::AsPlace as _;
+        let vec: &mut $crate::__private::Vec<_> = $vec.__as_place();
+        let init: *mut _ = $crate::place!(*$crate::__private::vec_spare(vec), $($t)+);
+        // SAFETY: init is the slot after the length, which has been
+        // initialized above
+        #[allow(unused_unsafe)]
+        let init = unsafe { $crate::__private::vec_push_at(vec, init) };
+        init
+    }};
+}
+
+/// Initialize a struct in-place at the end of a [`Vec`], and return a mutable
+/// reference, or an error if reserving the slot fails
+///
+/// This works exactly like [`place_vec_push!`], except allocation failure
+/// returns [`PlaceError::AllocFailed`] instead of aborting, for the layout of
+/// the element. No initializers are evaluated if it fails.
+///
+/// # Errors
+///
+/// If reserving the slot fails
+///
+/// # Examples
+///
+/// ```rust
+/// # use place::{try_place_vec_push, PlaceError};
+///
+/// struct MyCoolStruct {
+///     b: bool,
+///     s: String,
+/// }
+///
+/// let mut vec = Vec::new();
+///
+/// let x: Result<&mut MyCoolStruct, PlaceError> = try_place_vec_push!(
+///     vec,
+///     MyCoolStruct {
+///         b: true,
+///         s: String::from("works"),
+///     }
+/// );
+/// assert!(x.unwrap().b);
+/// assert_eq!(vec.len(), 1);
+/// ```
+///
+/// [`Vec`]: alloc::vec::Vec
+/// [`PlaceError::AllocFailed`]: crate::PlaceError::AllocFailed
+/// [`place_vec_push!`]: crate::place_vec_push
+#[macro_export]
+macro_rules! try_place_vec_push {
+    ($vec:expr, $($t:tt)+) => {{
+        use $crate::__private::AsPlace as _;
+        let vec: &mut $crate::__private::Vec<_> = $vec.__as_place();
+        match $crate::__private::try_vec_spare(vec) {
+            ::core::result::Result::Ok(slot) => {
+                let init: *mut _ = $crate::place!(*slot, $($t)+);
+                // SAFETY: See `place_vec_push!`
+                #[allow(unused_unsafe)]
+                let init = unsafe { $crate::__private::vec_push_at(vec, init) };
+                ::core::result::Result::Ok(init)
+            }
+            ::core::result::Result::Err(e) => ::core::result::Result::Err(e),
+        }
+    }};
+}
+
+/// Reserve, and return, the slot after the length of `vec`
+pub fn vec_spare<T>(vec: &mut Vec<T>) -> &mut MaybeUninit<T> {
+    vec.reserve(1);
+    &mut vec.spare_capacity_mut()[0]
+}
+
+/// Reserve, and return, the slot after the length of `vec`, if it can be
+/// allocated
+///
+/// # Errors
+///
+/// If reserving the slot fails
+pub fn try_vec_spare<T>(vec: &mut Vec<T>) -> Result<&mut MaybeUninit<T>, PlaceError> {
+    vec.try_reserve(1).map_err(|_| PlaceError::AllocFailed {
+        layout: Layout::new::<T>(),
+    })?;
+    Ok(&mut vec.spare_capacity_mut()[0])
+}
+
+/// Include the slot after the length of `vec`, returning it
+///
+/// The reference is derived from `ptr`, rather than indexing `vec`, so that
+/// pointers from `field!` into the value stay valid. Only the length of `vec`
+/// is written, not its elements.
+///
+/// # Safety
+///
+/// `ptr` must be the slot after the length, from [`vec_spare`], and
+/// initialized
+pub unsafe fn vec_push_at<T>(vec: &mut Vec<T>, ptr: *mut T) -> &mut T {
+    let len = vec.len();
+    // SAFETY: Caller ensures the slot at len is reserved and initialized
+    unsafe { vec.set_len(len + 1) };
+    // SAFETY: Caller ensures ptr is that slot, which is now in vec
+    unsafe { &mut *ptr }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{explode, DropCount},
+        PlaceError,
+    };
+    use alloc::{string::String, vec::Vec};
+    use core::{
+        alloc::Layout,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    struct Item<'a> {
+        name: String,
+        count: DropCount<'a>,
+    }
+
+    #[test]
+    fn vec_push() {
+        let drops = AtomicUsize::new(0);
+        let mut vec = Vec::new();
+
+        for i in 0..5 {
+            let x: &mut Item = place_vec_push!(
+                vec,
+                Item {
+                    name: i.to_string(),
+                    count: DropCount(&drops),
+                }
+            );
+            assert_eq!(x.name, i.to_string());
+        }
+        // Through a reference too
+        let r = &mut vec;
+        place_vec_push!(
+            r,
+            Item {
+                name: String::from("last"),
+                count: DropCount(&drops),
+            }
+        );
+        let names: Vec<_> = vec.iter().map(|x| x.name.as_str()).collect();
+        assert_eq!(names, ["0", "1", "2", "3", "4", "last"]);
+
+        drop(vec);
+        assert_eq!(drops.load(Ordering::Relaxed), 6);
+    }
+
+    struct SelfRef {
+        value: u32,
+        this: *const u32,
+    }
+
+    #[test]
+    fn vec_push_field() {
+        let mut vec = Vec::with_capacity(1);
+
+        let x: &mut SelfRef = place_vec_push!(
+            vec,
+            SelfRef {
+                value: 1,
+                this: crate::field!(value),
+            }
+        );
+        assert!(core::ptr::eq(x.this, &x.value));
+        // SAFETY: this points to value, which is initialized
+        assert_eq!(unsafe { *x.this }, 1);
+    }
+
+    #[test]
+    fn vec_push_panic() {
+        let drops = AtomicUsize::new(0);
+        let mut vec = Vec::new();
+        place_vec_push!(
+            vec,
+            Item {
+                name: String::from("first"),
+                count: DropCount(&drops),
+            }
+        );
+
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            place_vec_push!(
+                vec,
+                Item {
+                    count: DropCount(&drops),
+                    name: explode(),
+                }
+            );
+        }));
+        assert!(res.is_err());
+        assert_eq!(vec.len(), 1);
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+
+        drop(vec);
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn try_vec_push() {
+        let drops = AtomicUsize::new(0);
+        let mut vec = Vec::new();
+
+        let x = try_place_vec_push!(
+            &mut vec,
+            Item {
+                name: String::from("first"),
+                count: DropCount(&drops),
+            }
+        );
+        assert_eq!(x.unwrap().name, "first");
+        assert_eq!(vec.len(), 1);
+
+        drop(vec);
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+
+    struct Huge {
+        // Larger than any address space
+        data: [u8; 1 << 60],
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn try_vec_push_fail() {
+        let mut vec: Vec<Huge> = Vec::new();
+
+        // Nothing is evaluated when it fails
+        let x = try_place_vec_push!(
+            vec,
+            Huge {
+                data: emplace_fn!(|buf| crate::place_array!(*buf, |_| explode())),
+            }
+        );
+        assert_eq!(
+            x.err(),
+            Some(PlaceError::AllocFailed {
+                layout: Layout::new::<Huge>()
+            })
+        );
+        assert!(vec.is_empty());
+    }
+}